@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_quinnet::client::certificate::{
+    CertInteractionEvent, CertVerificationStatus, CertVerifierAction, CertVerifierBehaviour,
+    KnownHosts, TrustOnFirstUseConfig,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{Storage, StoragePath};
+
+/// Certificate fingerprints of servers trusted on the first connection
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct KnownServers {
+    /// Base64 fingerprint by server name
+    pub fingerprints: HashMap<String, String>,
+}
+
+impl StoragePath for KnownServers {
+    fn path() -> impl AsRef<std::path::Path> + Send + 'static {
+        "known_servers.toml"
+    }
+}
+
+/// Server whose certificate no longer matches the trusted one or couldn't be checked
+pub struct ChangedCertificate {
+    pub server_name: String,
+    /// Trusted fingerprint, `None` if the known servers couldn't be read
+    pub known: Option<String>,
+    pub received: String,
+}
+
+/// Last connection aborted because of a changed or unverifiable certificate
+#[derive(Resource, Default)]
+pub struct CertificateWarning(pub Option<ChangedCertificate>);
+
+impl CertificateWarning {
+    /// Trust the new certificate from now on
+    pub fn accept(&mut self, known_servers: &mut Storage<KnownServers>) {
+        let (Some(changed), Some(known)) = (self.0.take(), known_servers.get()) else {
+            return;
+        };
+        known
+            .fingerprints
+            .insert(changed.server_name, changed.received);
+        known_servers.queue_save();
+    }
+}
+
+pub struct CertificatePlugin;
+
+impl Plugin for CertificatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CertificateWarning>()
+            .add_systems(Startup, add_known_servers)
+            .add_systems(Update, verify_certificates);
+    }
+}
+
+fn add_known_servers(mut commands: Commands) {
+    commands.init_resource::<Storage<KnownServers>>();
+}
+
+/// Leave every decision to [`verify_certificates`], so fingerprints are kept in client storage
+pub fn trust_on_first_use() -> TrustOnFirstUseConfig {
+    let statuses = [
+        CertVerificationStatus::UnknownCertificate,
+        CertVerificationStatus::UntrustedCertificate,
+        CertVerificationStatus::TrustedCertificate,
+    ];
+    TrustOnFirstUseConfig {
+        known_hosts: KnownHosts::Store(HashMap::new()),
+        verifier_behaviour: statuses
+            .into_iter()
+            .map(|status| (status, CertVerifierBehaviour::RequestClientAction))
+            .collect(),
+    }
+}
+
+fn verify_certificates(
+    mut events: EventReader<CertInteractionEvent>,
+    mut known_servers: ResMut<Storage<KnownServers>>,
+    mut warning: ResMut<CertificateWarning>,
+) {
+    for event in events.read() {
+        let server_name = event.info.server_name.to_string();
+        let received = event.info.fingerprint.to_base64();
+        let action = match known_servers.get() {
+            Some(known) => match known.fingerprints.get(&server_name) {
+                Some(fingerprint) if *fingerprint == received => CertVerifierAction::TrustOnce,
+                Some(fingerprint) => {
+                    warn!(
+                        "Certificate of {} changed from {} to {}, aborting connection",
+                        server_name, fingerprint, received
+                    );
+                    warning.0 = Some(ChangedCertificate {
+                        server_name,
+                        known: Some(fingerprint.clone()),
+                        received,
+                    });
+                    CertVerifierAction::AbortConnection
+                }
+                None => {
+                    info!("Trusting certificate {} of {}", received, server_name);
+                    known.fingerprints.insert(server_name, received);
+                    known_servers.queue_save();
+                    CertVerifierAction::TrustOnce
+                }
+            },
+            None => {
+                warn!(
+                    "Known servers unavailable, can't verify {}, aborting connection",
+                    server_name
+                );
+                warning.0 = Some(ChangedCertificate {
+                    server_name,
+                    known: None,
+                    received,
+                });
+                CertVerifierAction::AbortConnection
+            }
+        };
+        if let Err(e) = event.apply_cert_verifier_action(action) {
+            warn!("Failed to verify certificate due to error {}", e);
+        }
+    }
+}
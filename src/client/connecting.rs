@@ -10,21 +10,33 @@ use bevy_quinnet::client::{
 use common::{
     bingo::Board,
     protocol::{ClientMessage, ServerMessage},
-    teams::Team,
     BoardRes, ConfMode, ConfPrompts,
 };
 
-use crate::{fit_text::PromptLayoutCache, states::AppState, Clients};
+use crate::{
+    certificates::{self, CertificateWarning},
+    fit_text::PromptLayoutCache,
+    game_ui::Announcement,
+    proofs::ProofViewer,
+    states::AppState,
+    Clients,
+};
 
 #[derive(Event)]
 pub struct StartConnection {
     pub username: String,
     pub addr: SocketAddr,
+    pub password: Option<String>,
+    pub room_password: Option<String>,
 }
 
 #[derive(Event)]
 pub struct StopConnection;
 
+/// Reason the server gave for closing the last connection
+#[derive(Resource, Default)]
+pub struct DisconnectReason(pub Option<String>);
+
 pub struct ConnectionPlugin;
 
 impl Plugin for ConnectionPlugin {
@@ -32,6 +44,7 @@ impl Plugin for ConnectionPlugin {
         app.add_event::<StartConnection>()
             .add_event::<StopConnection>()
             .add_event::<TeamWon>()
+            .init_resource::<DisconnectReason>()
             .add_plugins(QuinnetClientPlugin::default())
             .add_systems(
                 Update,
@@ -49,8 +62,12 @@ fn start_connection(
     mut client: ResMut<Client>,
     mut state: ResMut<NextState<AppState>>,
     mut events: EventReader<StartConnection>,
+    mut disconnect_reason: ResMut<DisconnectReason>,
+    mut certificate_warning: ResMut<CertificateWarning>,
 ) {
     for event in events.read() {
+        disconnect_reason.0 = None;
+        certificate_warning.0 = None;
         state.set(AppState::Playing);
         client
             .open_connection(
@@ -58,13 +75,15 @@ fn start_connection(
                     event.addr,
                     SocketAddr::from_str("0.0.0.0:0").unwrap(),
                 ),
-                CertificateVerificationMode::SkipVerification,
+                CertificateVerificationMode::TrustOnFirstUse(certificates::trust_on_first_use()),
             )
             .unwrap();
         client
             .connection()
             .send_message(ClientMessage::Join {
                 name: event.username.clone(),
+                password: event.password.clone(),
+                room_password: event.room_password.clone(),
             })
             .unwrap()
     }
@@ -93,7 +112,7 @@ pub fn stop_connection(
 }
 
 #[derive(Event)]
-pub struct TeamWon(Team);
+pub struct TeamWon;
 
 #[allow(clippy::too_many_arguments)]
 fn handle_messages(
@@ -105,6 +124,9 @@ fn handle_messages(
     mut prompts_conf: ResMut<ConfPrompts>,
     mut events: EventWriter<StopConnection>,
     mut cache: ResMut<PromptLayoutCache>,
+    mut proof_viewer: ResMut<ProofViewer>,
+    mut disconnect_reason: ResMut<DisconnectReason>,
+    mut announcement: ResMut<Announcement>,
 ) {
     loop {
         let result = client.connection_mut().receive_message::<ServerMessage>();
@@ -116,6 +138,9 @@ fn handle_messages(
                 &mut mode_conf,
                 &mut prompts_conf,
                 &mut cache,
+                &mut proof_viewer,
+                &mut disconnect_reason,
+                &mut announcement,
                 msg,
             ),
             Ok(None) => break,
@@ -127,6 +152,7 @@ fn handle_messages(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_single_message(
     team_won: &mut EventWriter<TeamWon>,
     board: &mut Board,
@@ -134,6 +160,9 @@ fn handle_single_message(
     mode_conf: &mut ConfMode,
     prompts_conf: &mut ConfPrompts,
     cache: &mut PromptLayoutCache,
+    proof_viewer: &mut ProofViewer,
+    disconnect_reason: &mut DisconnectReason,
+    announcement: &mut Announcement,
     msg: ServerMessage,
 ) {
     match msg {
@@ -143,6 +172,15 @@ fn handle_single_message(
         ServerMessage::SetClients(new_clients) => {
             clients.data = new_clients;
         }
+        ServerMessage::SetCoHostPermissions(permissions) => {
+            clients.co_host_permissions = permissions;
+        }
+        ServerMessage::SetTeamSettings(settings) => {
+            board.config.teams = settings;
+        }
+        ServerMessage::SetTeamStyles(styles) => {
+            board.config.team_styles = styles;
+        }
         ServerMessage::SetMode(mode) => {
             board.config.mode = mode.clone();
             mode_conf.mode = mode;
@@ -158,9 +196,31 @@ fn handle_single_message(
         }
         ServerMessage::SetActivity(activity) => {
             board.activity = activity;
-            if let Some(team) = board.check_win() {
-                team_won.send(TeamWon(team));
+            if board.check_win().is_some() {
+                team_won.send(TeamWon);
             }
         }
+        ServerMessage::ProofHeader { claim, header } => {
+            proof_viewer.receive_header(claim, header);
+        }
+        ServerMessage::ProofChunk { claim, data } => {
+            proof_viewer.receive_chunk(claim, &data);
+        }
+        ServerMessage::Announcement(text) => {
+            announcement.0 = Some(text);
+        }
+        ServerMessage::ServerShutdown {
+            reason,
+            reconnect_after,
+        } => {
+            let mut text = format!("Server shut down: {}", reason);
+            if let Some(secs) = reconnect_after {
+                text += &format!("\nTry reconnecting in {} seconds", secs);
+            }
+            disconnect_reason.0 = Some(text);
+        }
+        ServerMessage::JoinRejected(reason) => {
+            disconnect_reason.0 = Some(format!("Couldn't join: {}", reason));
+        }
     }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use bevy::{
     audio::Volume,
     prelude::*,
@@ -6,19 +8,25 @@ use bevy::{
 };
 use bevy_egui::EguiContext;
 use bevy_quinnet::client::Client;
-use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
 
 use common::{
-    bingo::{Board, BoardPrompts, GameMode, WinCondition},
+    bingo::{
+        format_match_time, Board, BoardConfig, BoardPrompts, Edge, FieldClaim, FieldKind, GameMode,
+        StealRules, WinCondition,
+    },
+    export::{self, ExportFormat},
     protocol::{ClientMessage, ClientProps},
-    teams::Team,
+    roles::{CoHostPermissions, Permission, Role},
+    teams::{MarkerLayout, Team, TeamSettings, TeamStyles, MAX_TEAMS, MAX_TEAM_NAME_LEN},
     BoardRes, ConfMode, ConfPrompts,
 };
 
 use crate::{
     connecting::{StopConnection, TeamWon},
     fit_text::PromptLayoutCache,
+    prompt_editor::{randomize_prompts, PromptEditor, PromptsView, LOCK_ICON},
+    proofs::{proof_draft_ui, send_proof, ProofDraft, ProofViewer},
     scoped::Scoped,
     states::AppState,
     storage::{Storage, StoragePath},
@@ -34,14 +42,13 @@ impl Plugin for GameUiPlugin {
             .add_systems(OnExit(AppState::Playing), remove_resources)
             .add_systems(
                 Update,
-                (
-                    create_bingo_window,
-                    game_menu_ui,
-                    bingo_board_ui,
-                    play_win_sfx,
-                    resize_window,
-                )
+                (game_menu_ui, bingo_board_ui, play_win_sfx, announcement_ui)
                     .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (create_bingo_window, resize_window)
+                    .run_if(in_state(AppState::Playing).or_else(in_state(AppState::Replay))),
             );
     }
 }
@@ -53,6 +60,12 @@ fn add_resources(mut commands: Commands) {
     commands.init_resource::<ConfPrompts>();
     commands.init_resource::<PromptLayoutCache>();
     commands.init_resource::<Storage<PromptsString>>();
+    commands.init_resource::<PromptEditor>();
+    commands.init_resource::<PendingSteal>();
+    commands.init_resource::<Announcement>();
+    commands.init_resource::<BoardExport>();
+    commands.init_resource::<RoomPasswordDraft>();
+    commands.init_resource::<TeamStylesDraft>();
 }
 
 fn remove_resources(mut commands: Commands) {
@@ -62,42 +75,220 @@ fn remove_resources(mut commands: Commands) {
     commands.remove_resource::<ConfPrompts>();
     commands.remove_resource::<PromptLayoutCache>();
     commands.remove_resource::<Storage<PromptsString>>();
+    commands.remove_resource::<PromptEditor>();
+    commands.remove_resource::<PendingSteal>();
+    commands.remove_resource::<Announcement>();
+    commands.remove_resource::<BoardExport>();
+    commands.remove_resource::<RoomPasswordDraft>();
+    commands.remove_resource::<TeamStylesDraft>();
+}
+
+/// Team name in the team's color
+fn team_label(styles: &TeamStyles, team: Team) -> egui::RichText {
+    egui::RichText::new(styles.name(team)).color(styles.color(team))
 }
 
-fn team_to_ui(ui: &mut egui::Ui, value: &mut Option<Team>, team: Option<Team>) -> egui::Response {
+fn team_to_ui(
+    ui: &mut egui::Ui,
+    value: &mut Option<Team>,
+    team: Option<Team>,
+    styles: &TeamStyles,
+) -> egui::Response {
     let label = match team {
-        Some(team) => egui::RichText::new(format!("{:?}", team)).color(team.color()),
+        Some(team) => team_label(styles, team),
         None => egui::RichText::new("Spectator"),
     };
     ui.selectable_value(value, team, label)
 }
 
-fn teams_selector(ui: &mut egui::Ui, client_props: &mut ClientProps) -> bool {
-    let mut clicked = false;
+/// Team picker, returns the message to send when the player picked a team
+fn teams_selector(
+    ui: &mut egui::Ui,
+    client_props: &mut ClientProps,
+    settings: &TeamSettings,
+    styles: &TeamStyles,
+    sizes: &HashMap<Team, usize>,
+) -> Option<ClientMessage> {
+    let mut selection = None;
     ui.separator();
     ui.label("Team");
     ui.separator();
 
-    clicked |= ui
-        .vertical_centered_justified(|ui| team_to_ui(ui, &mut client_props.team, None))
-        .inner
-        .clicked();
+    ui.vertical_centered_justified(|ui| {
+        if team_to_ui(ui, &mut client_props.team, None, styles).clicked() {
+            selection = Some(ClientMessage::ChangeTeam(None));
+        }
+        if client_props.team.is_none() && ui.button("Random team").clicked() {
+            selection = Some(ClientMessage::JoinRandomTeam);
+        }
+    });
 
     egui::Grid::new("Game Team Grid").show(ui, |ui| {
-        let mut iter = Team::iter();
-        for _ in 0..2 {
-            for _ in 0..4 {
-                clicked |=
-                    team_to_ui(ui, &mut client_props.team, Some(*iter.next().unwrap())).clicked();
+        for (i, team) in settings.active().enumerate() {
+            let size = sizes.get(&team).copied().unwrap_or(0);
+            let joinable = client_props.team == Some(team) || settings.has_room(size);
+            let players = match settings.max_size {
+                Some(max_size) => format!("{}/{} players", size, max_size),
+                None => format!("{} players", size),
+            };
+            let clicked = ui
+                .add_enabled_ui(joinable, |ui| {
+                    team_to_ui(ui, &mut client_props.team, Some(team), styles)
+                        .on_hover_text(players)
+                })
+                .inner
+                .clicked();
+            if clicked {
+                selection = Some(ClientMessage::ChangeTeam(client_props.team));
+            }
+            if i % 4 == 3 {
+                ui.end_row();
+            }
+        }
+    });
+    selection
+}
+
+/// Largest team size offered in the team settings
+const MAX_TEAM_SIZE: u8 = 16;
+
+/// Team names and colors edited by the host, replaced by the room's while unchanged
+#[derive(Resource, Default)]
+struct TeamStylesDraft {
+    styles: TeamStyles,
+    changed: bool,
+}
+
+fn team_styles_ui(
+    ui: &mut egui::Ui,
+    client: &Client,
+    settings: &TeamSettings,
+    styles: &TeamStyles,
+    draft: &mut TeamStylesDraft,
+) {
+    if !draft.changed {
+        draft.styles = styles.clone();
+    }
+    egui::Grid::new("Team Styles Grid").show(ui, |ui| {
+        for team in settings.active() {
+            let mut style = draft.styles.get(team);
+            let mut changed = ui
+                .color_edit_button_srgb(&mut style.color)
+                .on_hover_text(team.name())
+                .changed();
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut style.name).char_limit(MAX_TEAM_NAME_LEN))
+                .changed();
+            if changed {
+                draft.styles.set(team, style);
+                draft.changed = true;
             }
-            ui.end_row()
+            ui.end_row();
+        }
+    });
+    ui.horizontal(|ui| {
+        if ui.button("Default names and colors").clicked() {
+            draft.styles = TeamStyles::default();
+            draft.changed = true;
+        }
+        if ui
+            .add_enabled(draft.changed, egui::Button::new("Apply"))
+            .clicked()
+        {
+            client
+                .connection()
+                .try_send_message(ClientMessage::SetTeamStyles(draft.styles.clone()));
+            draft.changed = false;
+        }
+        if ui
+            .add_enabled(draft.changed, egui::Button::new("Cancel"))
+            .clicked()
+        {
+            draft.changed = false;
         }
     });
-    clicked
+}
+
+fn team_settings_ui(
+    ui: &mut egui::Ui,
+    client: &Client,
+    settings: TeamSettings,
+    styles: &TeamStyles,
+    styles_draft: &mut TeamStylesDraft,
+) {
+    ui.collapsing("Team settings", |ui| {
+        let mut settings = settings;
+        let mut changed = false;
+        egui::ComboBox::from_label("Teams")
+            .selected_text(settings.count.to_string())
+            .show_ui(ui, |ui| {
+                for count in 1..=MAX_TEAMS as u8 {
+                    changed |= ui
+                        .selectable_value(&mut settings.count, count, count.to_string())
+                        .clicked();
+                }
+            });
+        let selected_text = match settings.max_size {
+            Some(max_size) => max_size.to_string(),
+            None => "Unlimited".to_owned(),
+        };
+        egui::ComboBox::from_label("Max players per team")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                changed |= ui
+                    .selectable_value(&mut settings.max_size, None, "Unlimited")
+                    .clicked();
+                for max_size in 1..=MAX_TEAM_SIZE {
+                    changed |= ui
+                        .selectable_value(
+                            &mut settings.max_size,
+                            Some(max_size),
+                            max_size.to_string(),
+                        )
+                        .clicked();
+                }
+            });
+        if changed {
+            client
+                .connection()
+                .try_send_message(ClientMessage::SetTeamSettings(settings));
+        }
+        if ui.button("Auto-balance").clicked() {
+            client
+                .connection()
+                .try_send_message(ClientMessage::BalanceTeams);
+        }
+        ui.collapsing("Names and colors", |ui| {
+            team_styles_ui(ui, client, &settings, styles, styles_draft);
+        });
+    });
 }
 
 const HOST_ICON: &str = "★";
 const KICK_ICON: &str = "🗑";
+const BAN_ICON: &str = "🚫";
+/// Ban lengths offered to the host, `None` is permanent
+const BAN_DURATIONS: [(&str, Option<u64>); 3] = [
+    ("1 hour", Some(60 * 60)),
+    ("1 day", Some(24 * 60 * 60)),
+    ("Permanently", None),
+];
+const REFEREE_ICON: &str = "⚖";
+const CO_HOST_ICON: &str = "☆";
+const CASTER_ICON: &str = "🎙";
+
+/// Icon and name shown for an assignable role
+fn role_label(role: Role) -> (&'static str, &'static str) {
+    match role {
+        Role::CoHost => (CO_HOST_ICON, "Co-host"),
+        Role::Referee => (REFEREE_ICON, "Referee"),
+        Role::Caster => (CASTER_ICON, "Caster"),
+        _ => ("", ""),
+    }
+}
+const APPROVE_ICON: &str = "✔";
+const REJECT_ICON: &str = "✖";
+const DETAILS_ICON: &str = "🔍";
 
 #[derive(Debug, PartialEq, Eq)]
 enum FlatWinCondition {
@@ -124,6 +315,237 @@ impl FlatWinCondition {
     }
 }
 
+/// Replace win conditions unavailable in the game mode, returns whether it changed
+fn fit_win_condition(win_condition: &mut WinCondition, game_mode: GameMode) -> bool {
+    if !game_mode.is_exclusive() && *win_condition == WinCondition::Domination {
+        *win_condition = FlatWinCondition::InRow.unflatten();
+        return true;
+    }
+    false
+}
+
+/// Claims of every team with their authors, in claim order
+pub fn match_summary_ui(ui: &mut egui::Ui, board: &Board) {
+    let mut attributions = board.activity.attributions.iter().collect::<Vec<_>>();
+    if attributions.is_empty() {
+        return;
+    }
+    attributions.sort_by_key(|a| a.timestamp);
+
+    ui.collapsing("Match summary", |ui| {
+        egui::Grid::new("Match Summary Teams Grid").show(ui, |ui| {
+            for team in Team::iter() {
+                let claims = attributions
+                    .iter()
+                    .filter(|a| a.claim.team == *team)
+                    .count();
+                if claims == 0 {
+                    continue;
+                }
+                ui.label(team_label(&board.config.team_styles, *team));
+                ui.label(format!("{} claims", claims));
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        egui::Grid::new("Match Summary Claims Grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for attribution in attributions {
+                    let claim = attribution.claim;
+                    let time = attribution
+                        .timestamp
+                        .saturating_sub(board.activity.started_at);
+                    ui.label(format_match_time(time));
+                    let color = board.config.team_styles.color(claim.team);
+                    ui.label(egui::RichText::new(&attribution.username).color(color));
+                    let mut prompt = egui::RichText::new(board.prompt(claim.x, claim.y));
+                    if board.is_pending(claim.x, claim.y, &claim.team) {
+                        prompt = prompt.italics();
+                    }
+                    ui.label(prompt);
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+/// Format picked for exporting the board and the outcome of the last export
+#[derive(Resource)]
+struct BoardExport {
+    format: ExportFormat,
+    result: Option<Result<String, String>>,
+}
+
+impl Default for BoardExport {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Svg,
+            result: None,
+        }
+    }
+}
+
+fn board_export_ui(ui: &mut egui::Ui, board: &Board, board_export: &mut BoardExport) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source("Export Format")
+            .selected_text(board_export.format.to_string())
+            .show_ui(ui, |ui| {
+                for format in ExportFormat::ALL {
+                    ui.selectable_value(&mut board_export.format, format, format.to_string());
+                }
+            });
+        if ui.button("Export board").clicked() {
+            let path = export::default_path(board_export.format);
+            board_export.result = Some(
+                export::save(board, board_export.format, &path)
+                    .map(|_| format!("Saved to {}", path.display()))
+                    .map_err(|e| format!("Failed to export: {}", e)),
+            );
+        }
+    });
+    match &board_export.result {
+        Some(Ok(message)) => {
+            ui.label(message);
+        }
+        Some(Err(message)) => {
+            ui.colored_label(egui::Color32::RED, message);
+        }
+        None => {}
+    }
+}
+
+fn co_host_permissions_ui(ui: &mut egui::Ui, client: &Client, permissions: CoHostPermissions) {
+    ui.collapsing("Co-host permissions", |ui| {
+        let mut permissions = permissions;
+        let mut changed = false;
+        changed |= ui
+            .checkbox(&mut permissions.edit_board, "Edit board")
+            .changed();
+        changed |= ui
+            .checkbox(&mut permissions.reset, "Restart game")
+            .changed();
+        changed |= ui.checkbox(&mut permissions.kick, "Kick").changed();
+        if changed {
+            client
+                .connection()
+                .try_send_message(ClientMessage::SetCoHostPermissions(permissions));
+        }
+    });
+}
+
+/// Room password typed by the host, sent when confirmed
+#[derive(Resource, Default)]
+struct RoomPasswordDraft(String);
+
+fn room_password_ui(ui: &mut egui::Ui, client: &Client, draft: &mut RoomPasswordDraft) {
+    ui.horizontal(|ui| {
+        ui.label("Room password:");
+        ui.add(
+            egui::TextEdit::singleline(&mut draft.0)
+                .password(true)
+                .desired_width(100.0),
+        )
+        .on_hover_text("Required to join the room, leave empty to remove it");
+        if ui.button("Set").clicked() {
+            let password = Some(draft.0.clone()).filter(|p| !p.is_empty());
+            client
+                .connection()
+                .try_send_message(ClientMessage::SetRoomPassword(password));
+        }
+    });
+}
+
+/// Steal mode restrictions, returns whether they changed
+fn steal_rules_ui(ui: &mut egui::Ui, steal_rules: &mut StealRules) -> bool {
+    let mut changed = false;
+    egui::Grid::new("Steal Rules Grid").show(ui, |ui| {
+        ui.label("Redo prompt to steal");
+        changed |= ui
+            .checkbox(&mut steal_rules.require_recompletion, "")
+            .changed();
+        ui.end_row();
+
+        ui.label("Cooldown (s)");
+        changed |= ui
+            .add(egui::DragValue::new(&mut steal_rules.cooldown_secs).speed(0.1))
+            .changed();
+        ui.end_row();
+
+        let mut limited = steal_rules.max_steals.is_some();
+        if ui.checkbox(&mut limited, "Max steals").changed() {
+            steal_rules.max_steals = limited.then_some(3);
+            changed = true;
+        }
+        if let Some(max_steals) = &mut steal_rules.max_steals {
+            changed |= ui
+                .add(egui::DragValue::new(max_steals).speed(0.03))
+                .changed();
+        }
+        ui.end_row();
+    });
+    changed
+}
+
+/// Win condition kind selector with its parameters, returns whether it changed
+fn win_condition_ui(
+    ui: &mut egui::Ui,
+    win_condition: &mut WinCondition,
+    game_mode: GameMode,
+) -> bool {
+    let mut changed = false;
+    let mut flat_win_condition = FlatWinCondition::flatten(*win_condition);
+    ui.horizontal(|ui| {
+        changed |= ui
+            .selectable_value(
+                &mut flat_win_condition,
+                FlatWinCondition::InRow,
+                "N rows of M",
+            )
+            .clicked();
+        if game_mode.is_exclusive() {
+            changed |= ui
+                .selectable_value(
+                    &mut flat_win_condition,
+                    FlatWinCondition::Domination,
+                    "Domination",
+                )
+                .clicked();
+        }
+        changed |= ui
+            .selectable_value(
+                &mut flat_win_condition,
+                FlatWinCondition::FirstTo,
+                "First to N",
+            )
+            .clicked();
+    });
+    if changed {
+        *win_condition = flat_win_condition.unflatten();
+    }
+    egui::Grid::new("Win Condition Grid").show(ui, |ui| match win_condition {
+        WinCondition::InRow {
+            ref mut length,
+            ref mut rows,
+        } => {
+            ui.label("Row length");
+            changed |= ui.add(egui::DragValue::new(length).speed(0.03)).changed();
+            ui.end_row();
+
+            ui.label("Row count");
+            changed |= ui.add(egui::DragValue::new(rows).speed(0.03)).changed();
+            ui.end_row();
+        }
+        WinCondition::Domination => {}
+        WinCondition::FirstTo(ref mut n) => {
+            ui.label("First to");
+            changed |= ui.add(egui::DragValue::new(n).speed(0.03)).changed();
+            ui.end_row();
+        }
+    });
+    changed
+}
+
 #[derive(Clone, Serialize, Deserialize, Default)]
 struct PromptsString {
     prompts: String,
@@ -146,6 +568,12 @@ fn game_menu_ui(
     mut prompts_conf: ResMut<ConfPrompts>,
     mut prompts_str_storage: ResMut<Storage<PromptsString>>,
     mut cache: ResMut<PromptLayoutCache>,
+    mut editor: ResMut<PromptEditor>,
+    mut proof_draft: ResMut<ProofDraft>,
+    mut proof_viewer: ResMut<ProofViewer>,
+    mut board_export: ResMut<BoardExport>,
+    mut room_password: ResMut<RoomPasswordDraft>,
+    mut team_styles_draft: ResMut<TeamStylesDraft>,
 ) {
     let Ok(mut ctx) = egui_ctx.get_single_mut() else {
         return;
@@ -157,6 +585,7 @@ fn game_menu_ui(
 
     root_element(ctx.get_mut(), |ui| {
         let self_id = clients.self_id;
+        let permissions = clients.co_host_permissions;
         let Some(self_props) = clients.data.get(&self_id) else {
             return;
         };
@@ -169,50 +598,231 @@ fn game_menu_ui(
             for (client_id, client_props) in clients.data.iter() {
                 if client_props.is_host {
                     ui.label(HOST_ICON);
-                } else if self_props.is_host {
-                    let kick = ui.small_button(KICK_ICON).clicked();
-                    if kick {
-                        client
-                            .connection()
-                            .try_send_message(ClientMessage::Kick(*client_id));
-                    }
                 } else {
-                    ui.label("");
+                    ui.horizontal(|ui| {
+                        if self_props.can(Permission::Kick, &permissions) {
+                            let kick = ui.small_button(KICK_ICON).on_hover_text("Kick").clicked();
+                            if kick {
+                                client
+                                    .connection()
+                                    .try_send_message(ClientMessage::Kick(*client_id));
+                            }
+                        }
+                        if self_props.can(Permission::TransferHost, &permissions) {
+                            let make_host = ui
+                                .small_button(HOST_ICON)
+                                .on_hover_text("Make host")
+                                .clicked();
+                            if make_host {
+                                client
+                                    .connection()
+                                    .try_send_message(ClientMessage::TransferHost(*client_id));
+                            }
+                        }
+                        if !self_props.can(Permission::Ban, &permissions) {
+                            return;
+                        }
+                        ui.menu_button(BAN_ICON, |ui| {
+                            for (heading, ban_ip) in
+                                [("Username", false), ("Username and IP", true)]
+                            {
+                                ui.label(heading);
+                                for (label, duration_secs) in BAN_DURATIONS {
+                                    if ui.button(label).clicked() {
+                                        client.connection().try_send_message(ClientMessage::Ban {
+                                            client_id: *client_id,
+                                            duration_secs,
+                                            ban_ip,
+                                        });
+                                        ui.close_menu();
+                                    }
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Ban");
+                    });
                 }
                 let mut username = egui::RichText::new(&client_props.username);
                 if self_id == *client_id {
                     username = username.strong()
                 }
                 if let Some(team) = client_props.team {
-                    username = username.color(team.color());
+                    username = username.color(board.config.team_styles.color(team));
                 }
                 ui.label(username);
+                let manage_roles = self_props.can(Permission::ManageRoles, &permissions);
+                ui.horizontal(|ui| {
+                    for role in Role::ASSIGNABLE {
+                        if role == Role::CoHost && client_props.is_host {
+                            continue;
+                        }
+                        let (icon, name) = role_label(role);
+                        let mut granted = client_props.has_role(role);
+                        if !manage_roles {
+                            if granted {
+                                ui.label(icon).on_hover_text(name);
+                            }
+                            continue;
+                        }
+                        let toggled = ui
+                            .toggle_value(&mut granted, icon)
+                            .on_hover_text(name)
+                            .changed();
+                        if toggled {
+                            client
+                                .connection()
+                                .try_send_message(ClientMessage::SetRole {
+                                    client_id: *client_id,
+                                    role,
+                                    granted,
+                                });
+                        }
+                    }
+                });
                 ui.end_row();
             }
         });
 
+        if self_props.can(Permission::SetRoomPassword, &permissions) {
+            room_password_ui(ui, &client, &mut room_password);
+        }
+        if self_props.can(Permission::ManageRoles, &permissions) {
+            co_host_permissions_ui(ui, &client, permissions);
+        }
+
         let disconnect = ui.button("Disconnect").clicked();
         if disconnect {
             disconnect_events.send(StopConnection);
         }
 
+        if self_props.can(Permission::ReviewClaims, &permissions)
+            && !board.activity.pending.is_empty()
+        {
+            ui.separator();
+            ui.label("Pending claims");
+            ui.separator();
+            egui::Grid::new("Pending Claims Grid").show(ui, |ui| {
+                for claim in board.activity.pending.iter() {
+                    ui.label(team_label(&board.config.team_styles, claim.team));
+                    ui.label(board.prompt(claim.x, claim.y));
+                    if ui.small_button(DETAILS_ICON).clicked() {
+                        proof_viewer.open(claim.x, claim.y);
+                    }
+                    // The server only accepts approvals of other teams' claims
+                    let own_team = self_props.team == Some(claim.team);
+                    for (icon, approve) in [(APPROVE_ICON, true), (REJECT_ICON, false)] {
+                        let button = egui::Button::new(icon).small();
+                        if ui.add_enabled(!approve || !own_team, button).clicked() {
+                            client
+                                .connection()
+                                .try_send_message(ClientMessage::ReviewClaim {
+                                    claim: *claim,
+                                    approve,
+                                });
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        }
+
+        let mut team_sizes = HashMap::new();
+        for team in clients.data.values().filter_map(|props| props.team) {
+            *team_sizes.entry(team).or_insert(0) += 1;
+        }
         let self_props = clients.data.get_mut(&self_id).unwrap();
-        if teams_selector(ui, self_props) {
-            client
-                .connection()
-                .try_send_message(ClientMessage::ChangeTeam(self_props.team));
+        let team_settings = board.config.teams;
+        let team_styles = &board.config.team_styles;
+        if let Some(message) =
+            teams_selector(ui, self_props, &team_settings, team_styles, &team_sizes)
+        {
+            client.connection().try_send_message(message);
+        }
+        if self_props.can(Permission::ManageTeams, &permissions) {
+            team_settings_ui(
+                ui,
+                &client,
+                team_settings,
+                team_styles,
+                &mut team_styles_draft,
+            );
+        }
+        if self_props.team.is_some() {
+            proof_draft_ui(ui, &mut proof_draft);
         }
 
+        match_summary_ui(ui, &board);
+        board_export_ui(ui, &board, &mut board_export);
+
         ui.separator();
         ui.label("Game Settings");
         ui.separator();
         ui.label(format!("Game mode: {}", board.config.mode.game_mode));
+        if board.config.mode.require_approval {
+            ui.label("Claims need referee approval");
+        }
+        if board.config.mode.claim_cooldown_secs > 0 {
+            ui.label(format!(
+                "Claim cooldown: {}s",
+                board.config.mode.claim_cooldown_secs
+            ));
+            let cooldown_left = self_props
+                .team
+                .and_then(|team| board.claim_cooldown_left(&team));
+            if let Some(cooldown_left) = cooldown_left {
+                ui.label(format!(
+                    "Next claim in {:.0}s",
+                    cooldown_left.as_secs_f32().ceil()
+                ));
+            }
+        }
+        if let (GameMode::Invasion, Some(team)) = (board.config.mode.game_mode, self_props.team) {
+            ui.label(format!("Starting edge: {}", Edge::start_of(&team)));
+        }
+        if board.config.mode.game_mode == GameMode::Steal {
+            ui.label(format!("Steal rules: {}", board.config.mode.steal_rules));
+            let max_steals = board.config.mode.steal_rules.max_steals;
+            if let (Some(team), Some(max_steals)) = (self_props.team, max_steals) {
+                let steals_left = max_steals.saturating_sub(board.activity.steal_count(&team));
+                ui.label(format!("Steals left: {}", steals_left));
+            }
+        }
         ui.label(format!(
             "Win condition: {}",
             board.config.mode.win_condition
         ));
+        for team in Team::iter() {
+            if let Some(win_condition) = board.config.mode.team_win_conditions.get(team) {
+                let styles = &board.config.team_styles;
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} handicap: {}",
+                        styles.name(*team),
+                        win_condition
+                    ))
+                    .color(styles.color(*team)),
+                );
+            }
+        }
+
+        if !self_props.can(Permission::EditBoard, &permissions)
+            && self_props.can(Permission::ResetBoard, &permissions)
+        {
+            let restart = ui
+                .add_enabled(
+                    board.activity.activity.iter().any(|x| !x.is_empty()),
+                    egui::Button::new("Restart game"),
+                )
+                .clicked();
+            if restart {
+                client
+                    .connection()
+                    .try_send_message(ClientMessage::ResetActivity);
+            }
+        }
 
-        if self_props.is_host {
+        if self_props.can(Permission::EditBoard, &permissions) {
             // Mode
             let mut mode_game_mode_changed = false;
             ui.horizontal(|ui| {
@@ -222,73 +832,72 @@ fn game_menu_ui(
                 mode_game_mode_changed |= ui
                     .selectable_value(&mut mode_conf.game_mode, GameMode::Lockout, "Lockout")
                     .clicked();
+                mode_game_mode_changed |= ui
+                    .selectable_value(&mut mode_conf.game_mode, GameMode::Steal, "Steal")
+                    .clicked();
+                mode_game_mode_changed |= ui
+                    .selectable_value(&mut mode_conf.game_mode, GameMode::Invasion, "Invasion")
+                    .on_hover_text("Claim fields next to your starting edge or your fields")
+                    .clicked();
+            });
+            let mut mode_steal_rules_changed = false;
+            if mode_conf.game_mode == GameMode::Steal {
+                mode_steal_rules_changed |= steal_rules_ui(ui, &mut mode_conf.steal_rules);
+            }
+            let mut mode_claim_cooldown_changed = false;
+            egui::Grid::new("Claim Cooldown Grid").show(ui, |ui| {
+                ui.label("Claim cooldown (s)");
+                mode_claim_cooldown_changed |= ui
+                    .add(egui::DragValue::new(&mut mode_conf.claim_cooldown_secs).speed(0.1))
+                    .changed();
+                ui.end_row();
             });
+            let mode_require_approval_changed = ui
+                .checkbox(&mut mode_conf.require_approval, "Referee approval")
+                .changed();
 
             // Win condition
+            let game_mode = mode_conf.game_mode;
             let mut mode_win_condition_changed = false;
-            if mode_game_mode_changed
-                && mode_conf.game_mode != GameMode::Lockout
-                && mode_conf.win_condition == WinCondition::Domination
-            {
-                mode_conf.win_condition = FlatWinCondition::InRow.unflatten();
-                mode_win_condition_changed = true;
-            }
-            let mut flat_win_condition = FlatWinCondition::flatten(mode_conf.win_condition);
-            ui.horizontal(|ui| {
-                mode_win_condition_changed |= ui
-                    .selectable_value(
-                        &mut flat_win_condition,
-                        FlatWinCondition::InRow,
-                        "N rows of M",
-                    )
-                    .clicked();
-                if mode_conf.game_mode == GameMode::Lockout {
-                    mode_win_condition_changed |= ui
-                        .selectable_value(
-                            &mut flat_win_condition,
-                            FlatWinCondition::Domination,
-                            "Domination",
-                        )
-                        .clicked();
+            if mode_game_mode_changed {
+                mode_win_condition_changed |=
+                    fit_win_condition(&mut mode_conf.win_condition, game_mode);
+                for win_condition in mode_conf.team_win_conditions.values_mut() {
+                    mode_win_condition_changed |= fit_win_condition(win_condition, game_mode);
                 }
-                mode_win_condition_changed |= ui
-                    .selectable_value(
-                        &mut flat_win_condition,
-                        FlatWinCondition::FirstTo,
-                        "First to N",
-                    )
-                    .clicked();
-            });
-            if mode_win_condition_changed {
-                mode_conf.win_condition = flat_win_condition.unflatten();
             }
-            egui::Grid::new("Win Condition Grid").show(ui, |ui| {
-                match &mut mode_conf.win_condition {
-                    WinCondition::InRow {
-                        ref mut length,
-                        ref mut rows,
-                    } => {
-                        ui.label("Row length");
-                        mode_win_condition_changed |=
-                            ui.add(egui::DragValue::new(length).speed(0.03)).changed();
-                        ui.end_row();
-
-                        ui.label("Row count");
-                        mode_win_condition_changed |=
-                            ui.add(egui::DragValue::new(rows).speed(0.03)).changed();
-                        ui.end_row();
-                    }
-                    WinCondition::Domination => {}
-                    WinCondition::FirstTo(ref mut n) => {
-                        ui.label("First to");
-                        mode_win_condition_changed |=
-                            ui.add(egui::DragValue::new(n).speed(0.03)).changed();
-                        ui.end_row();
-                    }
+            mode_win_condition_changed |=
+                win_condition_ui(ui, &mut mode_conf.win_condition, game_mode);
+
+            // Handicaps
+            ui.collapsing("Team handicaps", |ui| {
+                for team in board.config.teams.active() {
+                    ui.push_id(team, |ui| {
+                        let mut enabled = mode_conf.team_win_conditions.contains_key(&team);
+                        let label = team_label(&board.config.team_styles, team);
+                        if ui.checkbox(&mut enabled, label).changed() {
+                            if enabled {
+                                let win_condition = mode_conf.win_condition;
+                                mode_conf.team_win_conditions.insert(team, win_condition);
+                            } else {
+                                mode_conf.team_win_conditions.remove(&team);
+                            }
+                            mode_win_condition_changed = true;
+                        }
+                        if let Some(win_condition) = mode_conf.team_win_conditions.get_mut(&team) {
+                            mode_win_condition_changed |=
+                                win_condition_ui(ui, win_condition, game_mode);
+                        }
+                    });
                 }
             });
-            mode_conf.changed |= mode_game_mode_changed || mode_win_condition_changed;
+            mode_conf.changed |= mode_game_mode_changed
+                || mode_steal_rules_changed
+                || mode_claim_cooldown_changed
+                || mode_require_approval_changed
+                || mode_win_condition_changed;
 
+            let (previous_x_size, previous_y_size) = (prompts_conf.x_size, prompts_conf.y_size);
             let mut prompts_size_changed = false;
             egui::Grid::new("Bingo Size Grid").show(ui, |ui| {
                 ui.label("Board width");
@@ -311,32 +920,33 @@ fn game_menu_ui(
                 ui.end_row();
             });
 
-            let randomize = ui.button("Randomize prompts").clicked();
+            let (randomize, edit) = ui
+                .horizontal(|ui| {
+                    let randomize = ui.button("Randomize prompts").clicked();
+                    let edit = ui
+                        .add_enabled(!prompts_conf.changed, egui::Button::new("Edit board"))
+                        .on_hover_text("Click a cell to edit it, drag to swap, right click to lock")
+                        .clicked();
+                    (randomize, edit)
+                })
+                .inner;
             if randomize | prompts_size_changed {
-                let mut prompts = prompts_str
-                    .prompts
-                    .split('\n')
-                    .filter_map(|x| {
-                        let x = x.trim();
-                        if x.is_empty() {
-                            None
-                        } else {
-                            Some(x.to_owned())
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                let prompt_count = prompts.len();
-                let target_prompt_count =
-                    prompts_conf.x_size as usize * prompts_conf.y_size as usize;
-                if target_prompt_count > prompt_count {
-                    prompts.extend(vec![String::new(); target_prompt_count - prompt_count]);
-                }
-                prompts.shuffle(&mut rand::thread_rng());
-                prompts.truncate(target_prompt_count);
-                prompts_conf.prompts.prompts = prompts;
+                editor.retain_in(prompts_conf.x_size, prompts_conf.y_size);
+                let previous = BoardPrompts {
+                    x_size: previous_x_size,
+                    y_size: previous_y_size,
+                    prompts: std::mem::take(&mut prompts_conf.prompts.prompts),
+                    fields: std::mem::take(&mut prompts_conf.prompts.fields),
+                };
+                randomize_prompts(
+                    &prompts_str.prompts,
+                    &previous,
+                    &mut prompts_conf,
+                    &editor.locked,
+                );
                 cache.clear();
             }
-            prompts_conf.changed |= prompts_size_changed || randomize;
+            prompts_conf.changed |= prompts_size_changed || randomize || edit;
 
             // Send update
             ui.horizontal(|ui| {
@@ -376,17 +986,29 @@ fn game_menu_ui(
                     mode_conf.changed = false;
                     **prompts_conf = board.config.prompts.clone();
                     prompts_conf.changed = false;
+                    editor.retain_in(prompts_conf.x_size, prompts_conf.y_size);
                 }
             });
 
             ui.separator();
             ui.label("Prompts");
             ui.separator();
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut editor.view, PromptsView::Pool, "Pool");
+                ui.selectable_value(&mut editor.view, PromptsView::Cells, "Cells");
+            });
             egui::ScrollArea::vertical()
                 .auto_shrink(false)
                 .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::VisibleWhenNeeded)
-                .show(ui, |ui| {
-                    prompt_str_changed |= ui.text_edit_multiline(&mut prompts_str.prompts).changed()
+                .show(ui, |ui| match editor.view {
+                    PromptsView::Pool => {
+                        prompt_str_changed |=
+                            ui.text_edit_multiline(&mut prompts_str.prompts).changed()
+                    }
+                    PromptsView::Cells => {
+                        prompts_conf.changed |=
+                            prompt_cells_list(ui, &mut prompts_conf, &mut editor, &board.config)
+                    }
                 });
         }
     });
@@ -396,6 +1018,76 @@ fn game_menu_ui(
     }
 }
 
+/// Selector of the special rule of a field
+fn field_kind_selector(
+    ui: &mut egui::Ui,
+    prompts: &mut BoardPrompts,
+    (x, y): (u8, u8),
+    config: &BoardConfig,
+) -> bool {
+    let styles = &config.team_styles;
+    let mut field = prompts.field(x, y);
+    let mut changed = false;
+    egui::ComboBox::from_id_source(("Field Kind", x, y))
+        .selected_text(match field {
+            FieldKind::PreClaimed(team) => format!("{} claimed", styles.name(team)),
+            _ => field.to_string(),
+        })
+        .show_ui(ui, |ui| {
+            changed |= ui
+                .selectable_value(&mut field, FieldKind::Normal, "Normal")
+                .clicked();
+            changed |= ui
+                .selectable_value(&mut field, FieldKind::Free, "Free")
+                .clicked();
+            for team in config.teams.active() {
+                let label = egui::RichText::new(format!("{} claimed", styles.name(team)))
+                    .color(styles.color(team));
+                changed |= ui
+                    .selectable_value(&mut field, FieldKind::PreClaimed(team), label)
+                    .clicked();
+            }
+        });
+    if changed {
+        prompts.set_field(x, y, field);
+    }
+    changed
+}
+
+/// Per-cell prompt fields with lock toggles and special rules
+fn prompt_cells_list(
+    ui: &mut egui::Ui,
+    prompts: &mut BoardPrompts,
+    editor: &mut PromptEditor,
+    config: &BoardConfig,
+) -> bool {
+    let mut changed = false;
+    egui::Grid::new("Prompt Cells Grid")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for y in 0..prompts.y_size {
+                for x in 0..prompts.x_size {
+                    let mut cell = egui::RichText::new(format!("{}, {}", x + 1, y + 1));
+                    if editor.selected == Some((x, y)) {
+                        cell = cell.strong();
+                    }
+                    ui.label(cell);
+
+                    let mut locked = editor.is_locked(x, y);
+                    if ui.checkbox(&mut locked, LOCK_ICON).changed() {
+                        editor.toggle_lock(x, y);
+                    }
+
+                    changed |= field_kind_selector(ui, prompts, (x, y), config);
+                    changed |= ui.text_edit_singleline(prompts.prompt_mut(x, y)).changed();
+                    ui.end_row();
+                }
+            }
+        });
+    changed
+}
+
 #[derive(Component)]
 struct BingoWindow;
 
@@ -416,6 +1108,7 @@ fn create_bingo_window(
     mut commands: Commands,
     window: Query<Entity, With<BingoWindow>>,
     board: Res<BoardRes>,
+    state: Res<State<AppState>>,
     mut camera: Query<&mut Camera, With<BingoWindowCamera>>,
 ) {
     if window.get_single().is_ok() {
@@ -426,7 +1119,7 @@ fn create_bingo_window(
 
     let window_id = commands
         .spawn((
-            Scoped(AppState::Playing),
+            Scoped(state.get().clone()),
             BingoWindow,
             Window {
                 title: "Bingo Board".to_owned(),
@@ -443,7 +1136,7 @@ fn create_bingo_window(
         camera.target = target;
     } else {
         commands.spawn((
-            Scoped(AppState::Playing),
+            Scoped(state.get().clone()),
             Camera2dBundle {
                 camera: Camera {
                     target,
@@ -455,13 +1148,22 @@ fn create_bingo_window(
     }
 }
 
+/// Field waiting for the player to confirm a steal
+#[derive(Resource, Default)]
+struct PendingSteal(Option<(u8, u8)>);
+
 pub const FIELD_SIZE: f32 = 120.0;
 pub const GAP_SIZE: f32 = 3.0;
 
+#[allow(clippy::too_many_arguments)]
 fn bingo_board_ui(
     mut egui_ctx: Query<&mut EguiContext, Without<PrimaryWindow>>,
     mut board: ResMut<BoardRes>,
-    prompts_conf: Res<ConfPrompts>,
+    mut prompts_conf: ResMut<ConfPrompts>,
+    mut editor: ResMut<PromptEditor>,
+    mut pending_steal: ResMut<PendingSteal>,
+    mut proof_draft: ResMut<ProofDraft>,
+    mut proof_viewer: ResMut<ProofViewer>,
     clients: Res<Clients>,
     client: Res<Client>,
     mut prompt_layout_cache: ResMut<PromptLayoutCache>,
@@ -473,6 +1175,7 @@ fn bingo_board_ui(
     let Some(client_props) = clients.data.get(&self_id) else {
         return;
     };
+    let styles = board.config.team_styles.clone();
 
     egui::CentralPanel::default()
         .frame(egui::Frame::none().inner_margin(GAP_SIZE))
@@ -486,71 +1189,185 @@ fn bingo_board_ui(
                     ui.style_mut().visuals.widgets.active.expansion = 0.0;
 
                     if prompts_conf.changed {
+                        editor.hovered = None;
                         for y in 0..prompts_conf.y_size {
                             for x in 0..prompts_conf.x_size {
-                                preview_bingo_field(
+                                prompts_conf.changed |= preview_bingo_field(
                                     ui,
-                                    &prompts_conf,
+                                    &mut prompts_conf,
+                                    &mut editor,
                                     (x, y),
                                     &mut prompt_layout_cache,
+                                    &styles,
                                 );
                             }
                             ui.end_row();
                         }
+                        if ui.input(|i| i.pointer.any_released()) {
+                            if let (Some(from), Some(to)) = (editor.dragged.take(), editor.hovered)
+                            {
+                                editor.swap(&mut prompts_conf, from, to);
+                            }
+                        }
                     } else {
+                        editor.clear_interaction();
                         for y in 0..board.config.prompts.y_size {
                             for x in 0..board.config.prompts.x_size {
-                                playable_bingo_field(
+                                let interaction = playable_bingo_field(
                                     ui,
                                     &mut board,
                                     client_props,
                                     &client,
                                     (x, y),
+                                    &mut pending_steal.0,
                                     &mut prompt_layout_cache,
                                 );
+                                if let Some(team) =
+                                    client_props.team.filter(|_| interaction.claimed)
+                                {
+                                    let claim = FieldClaim { team, x, y };
+                                    send_proof(&client, claim, &mut proof_draft);
+                                }
+                                if interaction.inspected
+                                    && client_props.can(
+                                        Permission::InspectProofs,
+                                        &clients.co_host_permissions,
+                                    )
+                                {
+                                    proof_viewer.open(x, y);
+                                }
                             }
                             ui.end_row();
                         }
                     }
                 });
         });
+
+    if let (Some((x, y)), Some(team)) = (pending_steal.0, client_props.team) {
+        let mut open = true;
+        egui::Window::new("Steal field")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx.get_mut(), |ui| {
+                ui.label(board.prompt(x, y));
+                ui.label("Did you complete this prompt again?");
+                ui.horizontal(|ui| {
+                    if ui.button("Steal").clicked() {
+                        if board.claim(x, y, team) {
+                            client
+                                .connection()
+                                .try_send_message(ClientMessage::UpdateActivity {
+                                    team,
+                                    x,
+                                    y,
+                                    is_active: true,
+                                });
+                            send_proof(&client, FieldClaim { team, x, y }, &mut proof_draft);
+                        }
+                        open = false;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        open = false;
+                    }
+                });
+            });
+        if !open {
+            pending_steal.0 = None;
+        }
+    }
 }
 
-fn playable_bingo_field(
-    ui: &mut egui::Ui,
-    board: &mut Board,
-    client_props: &ClientProps,
-    client: &Client,
-    (x, y): (u8, u8),
-    prompt_layout_cache: &mut PromptLayoutCache,
-) {
-    let team = client_props.team;
+/// Interactions with a playable field
+struct FieldInteraction {
+    /// The player claimed the field
+    claimed: bool,
+    /// The field was right clicked
+    inspected: bool,
+}
+
+/// Unpainted button of a field as seen by a player of the team
+fn field_button(board: &Board, (x, y): (u8, u8), team: Option<Team>) -> egui::Button<'static> {
     let mode = board.config.mode.game_mode;
+    let styles = &board.config.team_styles;
     let activity = board.activity(x, y);
     let mut widget = egui::Button::new("").rounding(0.0);
-    match mode {
-        GameMode::Lockout => {
-            if let Some(team) = activity.iter().next() {
-                widget = widget.fill(team.color());
+    match board.field(x, y) {
+        FieldKind::Normal => match mode {
+            GameMode::Lockout | GameMode::Steal | GameMode::Invasion => {
+                if let Some(team) = activity.iter().next() {
+                    widget = widget.fill(styles.color(*team));
+                }
             }
-        }
-        GameMode::FFA => {
-            if let Some(team) = team {
-                if activity.contains(&team) {
-                    widget = widget.fill(team.color());
+            GameMode::FFA => {
+                if let Some(team) = team {
+                    if activity.contains(&team) {
+                        widget = widget.fill(styles.color(team));
+                    }
                 }
             }
+        },
+        FieldKind::Free => widget = widget.fill(FREE_FIELD_COLOR),
+        FieldKind::PreClaimed(owner) => {
+            if mode.is_exclusive() || team == Some(owner) {
+                widget = widget.fill(styles.color(owner));
+            }
         }
     }
+    widget
+}
 
-    let size = egui::Vec2::new(FIELD_SIZE, FIELD_SIZE);
-    let button = ui.add_sized(size, widget);
-    let clicked = button.clicked();
+/// Claim authors and previous owners of a field
+fn field_hover_text(board: &Board, (x, y): (u8, u8)) -> Option<String> {
+    let styles = &board.config.team_styles;
+    let mut hover_lines = Vec::new();
+    for team in Team::iter() {
+        let claim = FieldClaim { team: *team, x, y };
+        if let Some(attribution) = board.activity.attribution(&claim) {
+            let time = attribution
+                .timestamp
+                .saturating_sub(board.activity.started_at);
+            let pending = if board.is_pending(x, y, team) {
+                " (pending)"
+            } else {
+                ""
+            };
+            hover_lines.push(format!(
+                "{}: {} at {}{}",
+                styles.name(*team),
+                attribution.username,
+                format_match_time(time),
+                pending
+            ));
+        }
+    }
+    let history = board.activity.history(board.offset(x, y));
+    if !history.is_empty() {
+        let owners = history
+            .iter()
+            .map(|team| styles.name(*team))
+            .collect::<Vec<_>>()
+            .join(", ");
+        hover_lines.push(format!("Stolen from {}", owners));
+    }
+    match hover_lines.is_empty() {
+        true => None,
+        false => Some(hover_lines.join("\n")),
+    }
+}
 
-    let pos = button.rect.left_top();
-    let size = button.rect.size();
-    let (x_step, y_step) = (size.x / 4.0, size.y / 4.0);
-    let painter = ui.painter_at(button.rect);
+/// Paint the prompt, special field markers and team markers over a field
+fn draw_field_contents(
+    ui: &egui::Ui,
+    rect: egui::Rect,
+    board: &Board,
+    (x, y): (u8, u8),
+    prompt_layout_cache: &mut PromptLayoutCache,
+) {
+    let pos = rect.left_top();
+    let size = rect.size();
+    let y_step = size.y / 4.0;
+    let painter = ui.painter_at(rect);
 
     prompt_layout_cache.draw_fitted_text(
         &painter,
@@ -561,32 +1378,95 @@ fn playable_bingo_field(
         ),
     );
 
-    for (i, team) in Team::iter().enumerate() {
+    let styles = &board.config.team_styles;
+    draw_field_kind(&painter, rect, board.field(x, y), styles);
+    let history = board.activity.history(board.offset(x, y));
+    draw_steal_history(&painter, rect, history, styles);
+
+    let activity = board.activity(x, y);
+    let shown_teams = board.shown_teams().collect::<Vec<_>>();
+    let layout = MarkerLayout::new(shown_teams.len());
+    for (i, team) in shown_teams.iter().enumerate() {
+        let ((x_offset, y_offset), (width, height)) = layout.marker(i);
+        let marker = egui::Rect::from_min_size(
+            pos + egui::vec2(x_offset * size.x, y_offset * size.y),
+            egui::vec2(width * size.x, height * size.y),
+        );
         if activity.contains(team) {
-            let x_offset = (i % 4) as f32 * x_step;
-            let y_offset = (i / 4) as f32 * y_step * 3.0;
-            let pos1 = pos + egui::Vec2::new(x_offset, y_offset);
-            let pos2 = pos1 + egui::Vec2::new(x_step, y_step);
-            painter.rect_filled(egui::Rect::from_two_pos(pos1, pos2), 0.0, team.color());
-        }
-    }
-
-    if client_props.team.is_some() && clicked {
-        let team = client_props.team.unwrap();
-        let mode = board.config.mode.game_mode;
-        let win = board.check_win();
-        let activity = board.activity_mut(x, y);
-        let was_active = activity.contains(&team);
-        let mut change = false;
-        match was_active {
-            true => {
-                activity.remove(&team);
-                change = true;
-            }
+            painter.rect_filled(marker, 0.0, styles.color(*team));
+        } else if board.is_pending(x, y, team) {
+            painter.rect_stroke(
+                marker.shrink(1.0),
+                0.0,
+                egui::Stroke::new(2.0, styles.color(*team)),
+            );
+        }
+    }
+}
+
+/// Read-only field as seen by a spectator
+pub fn spectated_bingo_field(
+    ui: &mut egui::Ui,
+    board: &Board,
+    field: (u8, u8),
+    prompt_layout_cache: &mut PromptLayoutCache,
+) {
+    let size = egui::Vec2::new(FIELD_SIZE, FIELD_SIZE);
+    let widget = field_button(board, field, None).sense(egui::Sense::hover());
+    let mut response = ui.add_sized(size, widget);
+    if let Some(text) = field_hover_text(board, field) {
+        response = response.on_hover_text(text);
+    }
+    draw_field_contents(ui, response.rect, board, field, prompt_layout_cache);
+}
+
+fn playable_bingo_field(
+    ui: &mut egui::Ui,
+    board: &mut Board,
+    client_props: &ClientProps,
+    client: &Client,
+    (x, y): (u8, u8),
+    pending_steal: &mut Option<(u8, u8)>,
+    prompt_layout_cache: &mut PromptLayoutCache,
+) -> FieldInteraction {
+    let team = client_props.team;
+    let mode = board.config.mode.game_mode;
+    let field = board.field(x, y);
+    let widget = field_button(board, (x, y), team);
+
+    let size = egui::Vec2::new(FIELD_SIZE, FIELD_SIZE);
+    let unreachable = match (mode, team) {
+        (GameMode::Invasion, Some(team)) => {
+            board.activity(x, y).is_empty()
+                && field == FieldKind::Normal
+                && !board.is_reachable(x, y, &team)
+        }
+        _ => false,
+    };
+    let mut button = ui.add_enabled(!unreachable, |ui: &mut egui::Ui| ui.add_sized(size, widget));
+    if let Some(text) = field_hover_text(board, (x, y)) {
+        button = button.on_hover_text(text);
+    }
+    let clicked = button.clicked();
+    let mut interaction = FieldInteraction {
+        claimed: false,
+        inspected: button.secondary_clicked(),
+    };
+
+    draw_field_contents(ui, button.rect, board, (x, y), prompt_layout_cache);
+
+    if let (Some(team), true) = (client_props.team, clicked) {
+        let was_active = board.is_active(x, y, &team) || board.is_pending(x, y, &team);
+        let change = match was_active {
+            true => board.unclaim(x, y, &team),
             false => {
-                if (mode != GameMode::Lockout || activity.is_empty()) && win.is_none() {
-                    activity.insert(team);
-                    change = true;
+                if board.config.mode.steal_rules.require_recompletion
+                    && board.can_steal(x, y, &team)
+                {
+                    *pending_steal = Some((x, y));
+                    false
+                } else {
+                    board.claim(x, y, team)
                 }
             }
         };
@@ -600,19 +1480,100 @@ fn playable_bingo_field(
                     y,
                     is_active: !was_active,
                 });
+            interaction.claimed = !was_active;
         }
     }
+
+    interaction
+}
+
+const FREE_FIELD_COLOR: egui::Color32 = egui::Color32::from_rgb(212, 175, 55);
+const FREE_ICON: &str = "★";
+const PRE_CLAIMED_ICON: &str = "📌";
+
+/// Marks special fields in the top middle of the field
+fn draw_field_kind(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    field: FieldKind,
+    styles: &TeamStyles,
+) {
+    let (icon, color) = match field {
+        FieldKind::Normal => return,
+        FieldKind::Free => (FREE_ICON, egui::Color32::WHITE),
+        FieldKind::PreClaimed(team) => (PRE_CLAIMED_ICON, styles.color(team)),
+    };
+    painter.text(
+        rect.center_top() + egui::vec2(0.0, rect.height() / 8.0),
+        egui::Align2::CENTER_CENTER,
+        icon,
+        egui::FontId::default(),
+        color,
+    );
+}
+
+/// Marks previous owners of a stolen field along its left edge, latest at the bottom
+fn draw_steal_history(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    history: &[Team],
+    styles: &TeamStyles,
+) {
+    let radius = 3.0;
+    let step = radius * 3.0;
+    let (top, bottom) = (
+        rect.top() + rect.height() / 4.0,
+        rect.bottom() - rect.height() / 4.0,
+    );
+    let visible = ((bottom - top) / step) as usize;
+    let skipped = history.len().saturating_sub(visible);
+    for (i, team) in history.iter().skip(skipped).enumerate() {
+        let center = egui::pos2(rect.left() + step / 2.0, top + step * (i as f32 + 0.5));
+        painter.circle_filled(center, radius, styles.color(*team));
+    }
 }
 
+/// Editable field of the preview board, returns whether the prompt changed
 fn preview_bingo_field(
     ui: &mut egui::Ui,
-    prompts: &BoardPrompts,
+    prompts: &mut BoardPrompts,
+    editor: &mut PromptEditor,
     (x, y): (u8, u8),
     prompt_layout_cache: &mut PromptLayoutCache,
-) {
-    let widget = egui::Button::new("").rounding(0.0);
+    styles: &TeamStyles,
+) -> bool {
     let size = egui::Vec2::new(FIELD_SIZE, FIELD_SIZE);
+
+    if editor.selected == Some((x, y)) {
+        let text_edit = egui::TextEdit::multiline(prompts.prompt_mut(x, y)).frame(false);
+        let response = ui.add_sized(size, text_edit);
+        if editor.focus_pending {
+            response.request_focus();
+            editor.focus_pending = false;
+        }
+        if response.lost_focus() && editor.selected == Some((x, y)) {
+            editor.selected = None;
+        }
+        return response.changed();
+    }
+
+    let widget = egui::Button::new("")
+        .rounding(0.0)
+        .sense(egui::Sense::click_and_drag());
     let button = ui.add_sized(size, widget);
+    if button.clicked() {
+        editor.select(x, y);
+    }
+    if button.secondary_clicked() {
+        editor.toggle_lock(x, y);
+    }
+    if button.drag_started() {
+        editor.dragged = Some((x, y));
+    }
+    if ui.rect_contains_pointer(button.rect) {
+        editor.hovered = Some((x, y));
+    }
+
     let pos = button.rect.left_top();
     let size = button.rect.size();
     let y_step = size.y / 4.0;
@@ -626,6 +1587,58 @@ fn preview_bingo_field(
             egui::vec2(size.x, size.y / 2.0),
         ),
     );
+
+    draw_field_kind(&painter, button.rect, prompts.field(x, y), styles);
+
+    if editor.is_locked(x, y) {
+        painter.text(
+            button.rect.right_top(),
+            egui::Align2::RIGHT_TOP,
+            LOCK_ICON,
+            egui::FontId::default(),
+            egui::Color32::WHITE,
+        );
+    }
+
+    if let Some(dragged) = editor.dragged {
+        if dragged == (x, y) || editor.hovered == Some((x, y)) {
+            painter.rect_stroke(
+                button.rect.shrink(1.0),
+                0.0,
+                egui::Stroke::new(2.0, egui::Color32::WHITE),
+            );
+        }
+    }
+
+    false
+}
+
+/// Latest message from the server admin, until dismissed
+#[derive(Resource, Default)]
+pub struct Announcement(pub Option<String>);
+
+fn announcement_ui(
+    mut egui_ctx: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut announcement: ResMut<Announcement>,
+) {
+    let Ok(mut ctx) = egui_ctx.get_single_mut() else {
+        return;
+    };
+    let Some(text) = &announcement.0 else {
+        return;
+    };
+    let mut dismissed = false;
+    egui::Window::new("Announcement")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx.get_mut(), |ui| {
+            ui.label(text);
+            dismissed = ui.button("OK").clicked();
+        });
+    if dismissed {
+        announcement.0 = None;
+    }
 }
 
 #[derive(Component)]
@@ -658,12 +1671,12 @@ fn play_win_sfx(
 fn resize_window(
     mut window: Query<&mut Window, With<BingoWindow>>,
     board: Res<BoardRes>,
-    prompts_conf: Res<ConfPrompts>,
+    prompts_conf: Option<Res<ConfPrompts>>,
 ) {
     if let Ok(mut window) = window.get_single_mut() {
-        let prompts = match prompts_conf.changed {
-            true => &prompts_conf,
-            false => &board.config.prompts,
+        let prompts = match prompts_conf.as_ref().filter(|conf| conf.changed) {
+            Some(conf) => &conf.prompts,
+            None => &board.config.prompts,
         };
         let (width, height) = size_from_board(prompts);
 
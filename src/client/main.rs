@@ -1,9 +1,13 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod certificates;
 mod connecting;
 mod fit_text;
 mod game_ui;
 mod menu_ui;
+mod prompt_editor;
+mod proofs;
+mod replay;
 mod scoped;
 mod states;
 mod storage;
@@ -15,18 +19,22 @@ use bevy::{
     window::{ExitCondition, WindowResolution},
 };
 use bevy_quinnet::shared::ClientId;
+use certificates::CertificatePlugin;
 use connecting::ConnectionPlugin;
 use game_ui::GameUiPlugin;
 use menu_ui::MenuUiPlugin;
+use proofs::ProofsPlugin;
+use replay::ReplayPlugin;
 use states::StatesPlugin;
 use std::collections::HashMap;
 
-use common::protocol::ClientProps;
+use common::{protocol::ClientProps, roles::CoHostPermissions};
 
 #[derive(Resource, Debug, Clone, Default)]
 struct Clients {
     data: HashMap<ClientId, ClientProps>,
     self_id: ClientId,
+    co_host_permissions: CoHostPermissions,
 }
 
 fn main() {
@@ -43,8 +51,11 @@ fn main() {
         }))
         .add_plugins(StatesPlugin)
         .add_plugins(ConnectionPlugin)
+        .add_plugins(CertificatePlugin)
         .add_plugins(MenuUiPlugin)
         .add_plugins(GameUiPlugin)
+        .add_plugins(ProofsPlugin)
+        .add_plugins(ReplayPlugin)
         .add_systems(Startup, setup_camera)
         .run();
 }
@@ -4,9 +4,11 @@ use bevy::{app::AppExit, prelude::*, window::PrimaryWindow};
 use bevy_egui::{EguiContext, EguiPlugin};
 
 use crate::{
-    connecting::StartConnection,
+    certificates::{CertificateWarning, KnownServers},
+    connecting::{DisconnectReason, StartConnection},
+    replay::{OpenReplay, ReplayLoadError},
     states::AppState,
-    storage::Storage,
+    storage::{Storage, StoragePath},
     ui::root_element,
     userdata::{Userdata, UserdataPlugin},
 };
@@ -45,11 +47,30 @@ fn add_validated_textbox(
     }))
 }
 
+/// Passwords typed in the menu, kept for the session even when they aren't saved
+#[derive(Default)]
+struct Passwords {
+    loaded: bool,
+    password: String,
+    room_password: String,
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    Some(text.to_owned()).filter(|text| !text.is_empty())
+}
+
+#[allow(clippy::too_many_arguments)]
 fn ui_root(
     mut egui_ctx: Query<&mut EguiContext, With<PrimaryWindow>>,
     mut app_exit: EventWriter<AppExit>,
     mut userdata_storage: ResMut<Storage<Userdata>>,
     mut client_connect: EventWriter<StartConnection>,
+    mut replay_open: EventWriter<OpenReplay>,
+    replay_error: Res<ReplayLoadError>,
+    disconnect_reason: Res<DisconnectReason>,
+    mut passwords: Local<Passwords>,
+    mut certificate_warning: ResMut<CertificateWarning>,
+    mut known_servers: ResMut<Storage<KnownServers>>,
 ) {
     let Ok(mut ctx) = egui_ctx.get_single_mut() else {
         return;
@@ -58,6 +79,12 @@ fn ui_root(
         return;
     };
 
+    if !passwords.loaded {
+        passwords.password = userdata.password.clone();
+        passwords.room_password = userdata.room_password.clone();
+        passwords.loaded = true;
+    }
+
     let addr: Option<SocketAddr> = userdata.addr.parse().ok();
     let valid_username = validate_username(&userdata.username);
     let mut userdata_changed = false;
@@ -77,7 +104,21 @@ fn ui_root(
                 userdata_changed |=
                     add_validated_textbox(ui, addr.is_some(), &mut userdata.addr).changed();
                 ui.end_row();
+
+                ui.label("Password:");
+                ui.add(egui::TextEdit::singleline(&mut passwords.password).password(true))
+                    .on_hover_text("Only needed if the server has a password");
+                ui.end_row();
+
+                ui.label("Room password:");
+                ui.add(egui::TextEdit::singleline(&mut passwords.room_password).password(true))
+                    .on_hover_text("Only needed if the host set a room password");
+                ui.end_row();
             });
+        userdata_changed |= ui
+            .checkbox(&mut userdata.remember_passwords, "Remember passwords")
+            .on_hover_text("Passwords are saved as plain text in userdata.toml")
+            .changed();
 
         ui.vertical_centered(|ui| {
             let connect = ui
@@ -90,8 +131,80 @@ fn ui_root(
                 client_connect.send(StartConnection {
                     username: userdata.username.clone(),
                     addr: addr.unwrap(),
+                    password: non_empty(&passwords.password),
+                    room_password: non_empty(&passwords.room_password),
+                });
+            }
+            if let Some(reason) = &disconnect_reason.0 {
+                ui.colored_label(egui::Color32::YELLOW, reason);
+            }
+            let mut accept_certificate = false;
+            if let Some(changed) = &certificate_warning.0 {
+                match &changed.known {
+                    Some(known) => {
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            egui::RichText::new("WARNING: SERVER CERTIFICATE CHANGED").strong(),
+                        );
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            format!(
+                                "The certificate of {} doesn't match the one trusted before. \
+                                 Someone may be intercepting the connection, only continue if \
+                                 the server owner confirms the new fingerprint.\n\
+                                 Trusted: {}\nReceived: {}",
+                                changed.server_name, known, changed.received
+                            ),
+                        );
+                        accept_certificate = ui.button("Trust new certificate").clicked();
+                    }
+                    None => {
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            egui::RichText::new("WARNING: SERVER CERTIFICATE NOT VERIFIED")
+                                .strong(),
+                        );
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            format!(
+                                "The trusted certificates couldn't be read from {}, so the \
+                                 certificate of {} can't be checked. Fix or remove the file \
+                                 and connect again.\nReceived: {}",
+                                KnownServers::path().as_ref().display(),
+                                changed.server_name,
+                                changed.received
+                            ),
+                        );
+                    }
+                }
+            }
+            if accept_certificate {
+                certificate_warning.accept(&mut known_servers);
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Replay:");
+                userdata_changed |= ui
+                    .text_edit_singleline(&mut userdata.replay_path)
+                    .on_hover_text("Path to a replay file")
+                    .changed();
+            });
+            let open_replay = ui
+                .add_enabled(
+                    !userdata.replay_path.trim().is_empty(),
+                    egui::Button::new("Open replay"),
+                )
+                .clicked();
+            if open_replay {
+                replay_open.send(OpenReplay {
+                    path: userdata.replay_path.trim().into(),
                 });
             }
+            if let Some(error) = &replay_error.0 {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            ui.separator();
 
             let exit = ui.button("Exit").clicked();
             if exit {
@@ -100,6 +213,16 @@ fn ui_root(
         });
     });
 
+    let (password, room_password) = match userdata.remember_passwords {
+        true => (passwords.password.clone(), passwords.room_password.clone()),
+        false => Default::default(),
+    };
+    if userdata.password != password || userdata.room_password != room_password {
+        userdata.password = password;
+        userdata.room_password = room_password;
+        userdata_changed = true;
+    }
+
     if userdata_changed {
         userdata_storage.queue_save();
     }
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use common::bingo::{BoardPrompts, FieldKind};
+
+pub const LOCK_ICON: &str = "🔒";
+
+/// Representation of prompts edited in the menu window
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum PromptsView {
+    /// Raw text pool used for randomizing
+    #[default]
+    Pool,
+    /// Per-cell list of the board prompts
+    Cells,
+}
+
+/// Host-side state of the structured prompt editor
+#[derive(Resource, Default)]
+pub struct PromptEditor {
+    pub view: PromptsView,
+    /// Cell edited inline on the preview board
+    pub selected: Option<(u8, u8)>,
+    /// Whether the selected cell should grab keyboard focus
+    pub focus_pending: bool,
+    /// Cell currently dragged on the preview board
+    pub dragged: Option<(u8, u8)>,
+    /// Cell below the pointer on the preview board
+    pub hovered: Option<(u8, u8)>,
+    /// Cells kept in place when randomizing
+    pub locked: HashSet<(u8, u8)>,
+}
+
+impl PromptEditor {
+    pub fn is_locked(&self, x: u8, y: u8) -> bool {
+        self.locked.contains(&(x, y))
+    }
+
+    pub fn toggle_lock(&mut self, x: u8, y: u8) {
+        if !self.locked.remove(&(x, y)) {
+            self.locked.insert((x, y));
+        }
+    }
+
+    pub fn select(&mut self, x: u8, y: u8) {
+        self.selected = Some((x, y));
+        self.focus_pending = true;
+    }
+
+    /// Drop all transient interaction state
+    pub fn clear_interaction(&mut self) {
+        self.selected = None;
+        self.focus_pending = false;
+        self.dragged = None;
+        self.hovered = None;
+    }
+
+    /// Forget locks of cells that no longer fit on the board
+    pub fn retain_in(&mut self, x_size: u8, y_size: u8) {
+        self.locked.retain(|&(x, y)| x < x_size && y < y_size);
+        if self
+            .selected
+            .is_some_and(|(x, y)| x >= x_size || y >= y_size)
+        {
+            self.selected = None;
+        }
+    }
+
+    /// Swap prompts of two cells, locks follow their prompts
+    pub fn swap(&mut self, prompts: &mut BoardPrompts, a: (u8, u8), b: (u8, u8)) {
+        if a == b {
+            return;
+        }
+        let (a_offset, b_offset) = (prompts.offset(a.0, a.1), prompts.offset(b.0, b.1));
+        prompts.prompts.swap(a_offset, b_offset);
+        let (a_locked, b_locked) = (self.locked.remove(&a), self.locked.remove(&b));
+        if a_locked {
+            self.locked.insert(b);
+        }
+        if b_locked {
+            self.locked.insert(a);
+        }
+    }
+}
+
+/// Split the pool text into non-empty prompts
+pub fn parse_pool(pool: &str) -> Vec<String> {
+    pool.split('\n')
+        .filter_map(|x| {
+            let x = x.trim();
+            if x.is_empty() {
+                None
+            } else {
+                Some(x.to_owned())
+            }
+        })
+        .collect()
+}
+
+/// Fill unlocked cells of `target` with shuffled prompts from the pool.
+/// Locked cells keep their prompt from `previous`, which may have a different size.
+/// Special fields stay at their coordinates.
+pub fn randomize_prompts(
+    pool: &str,
+    previous: &BoardPrompts,
+    target: &mut BoardPrompts,
+    locked: &HashSet<(u8, u8)>,
+) {
+    let mut pool = parse_pool(pool);
+    let mut kept = Vec::new();
+    for &(x, y) in locked {
+        let fits_previous = x < previous.x_size && y < previous.y_size;
+        let fits_target = x < target.x_size && y < target.y_size;
+        let Some(prompt) = previous
+            .prompts
+            .get(previous.offset(x, y))
+            .filter(|_| fits_previous && fits_target)
+        else {
+            continue;
+        };
+        if let Some(i) = pool.iter().position(|p| p == prompt) {
+            pool.swap_remove(i);
+        }
+        kept.push(((x, y), prompt.clone()));
+    }
+
+    let target_prompt_count = target.x_size as usize * target.y_size as usize;
+    let free_count = target_prompt_count - kept.len();
+    if free_count > pool.len() {
+        pool.extend(vec![String::new(); free_count - pool.len()]);
+    }
+    pool.shuffle(&mut rand::thread_rng());
+    pool.truncate(free_count);
+
+    target.prompts = vec![String::new(); target_prompt_count];
+    target.fields = vec![FieldKind::Normal; target_prompt_count];
+    for x in 0..target.x_size.min(previous.x_size) {
+        for y in 0..target.y_size.min(previous.y_size) {
+            target.set_field(x, y, previous.field(x, y));
+        }
+    }
+    let kept_cells = kept.iter().map(|(cell, _)| *cell).collect::<HashSet<_>>();
+    for ((x, y), prompt) in kept {
+        *target.prompt_mut(x, y) = prompt;
+    }
+    let mut pool = pool.into_iter();
+    for x in 0..target.x_size {
+        for y in 0..target.y_size {
+            if !kept_cells.contains(&(x, y)) {
+                *target.prompt_mut(x, y) = pool.next().unwrap_or_default();
+            }
+        }
+    }
+}
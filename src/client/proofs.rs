@@ -0,0 +1,301 @@
+use std::{collections::HashMap, path::Path};
+
+use bevy::{
+    prelude::*,
+    render::texture::{CompressedImageFormats, ImageSampler, ImageType},
+    window::PrimaryWindow,
+};
+use bevy_egui::{EguiContext, EguiUserTextures};
+use bevy_quinnet::client::Client;
+
+use common::{
+    bingo::FieldClaim,
+    proof::{Proof, ProofHeader, MAX_PROOF_IMAGE_SIZE, MAX_PROOF_NOTE_LENGTH},
+    protocol::ClientMessage,
+    teams::Team,
+    BoardRes,
+};
+
+use crate::states::AppState;
+
+/// Width of proof images in the claim detail panel
+const PREVIEW_WIDTH: f32 = 300.0;
+
+pub struct ProofsPlugin;
+
+impl Plugin for ProofsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Playing), add_resources)
+            .add_systems(OnExit(AppState::Playing), remove_resources)
+            .add_systems(Update, claim_details_ui.run_if(in_state(AppState::Playing)));
+    }
+}
+
+fn add_resources(mut commands: Commands) {
+    commands.init_resource::<ProofDraft>();
+    commands.init_resource::<ProofViewer>();
+}
+
+fn remove_resources(mut commands: Commands) {
+    commands.remove_resource::<ProofDraft>();
+    commands.remove_resource::<ProofViewer>();
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProofError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("image is larger than {MAX_PROOF_IMAGE_SIZE} bytes")]
+    TooLarge,
+}
+
+/// Proof attached to the next claim of the player
+#[derive(Resource, Default)]
+pub struct ProofDraft {
+    pub note: String,
+    pub image_path: String,
+}
+
+impl ProofDraft {
+    pub fn is_empty(&self) -> bool {
+        self.note.trim().is_empty() && self.image_path.trim().is_empty()
+    }
+
+    fn read_image(&self) -> Result<Option<(String, Vec<u8>)>, ProofError> {
+        let path = Path::new(self.image_path.trim());
+        if path.as_os_str().is_empty() {
+            return Ok(None);
+        }
+        if std::fs::metadata(path)?.len() > MAX_PROOF_IMAGE_SIZE as u64 {
+            return Err(ProofError::TooLarge);
+        }
+        let data = std::fs::read(path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Some((name, data)))
+    }
+}
+
+/// Send the drafted proof for a new claim and clear the draft
+pub fn send_proof(client: &Client, claim: FieldClaim, draft: &mut ProofDraft) {
+    if draft.is_empty() {
+        return;
+    }
+    let image = match draft.read_image() {
+        Ok(image) => image,
+        Err(e) => {
+            warn!("Failed to attach proof image due to error {}", e);
+            return;
+        }
+    };
+    let header = ProofHeader {
+        note: draft.note.trim().to_owned(),
+        image_name: image.as_ref().map(|(name, _)| name.clone()),
+        image_size: image.as_ref().map_or(0, |(_, data)| data.len() as u32),
+    };
+    let proof = Proof {
+        header: header.clone(),
+        image: image.map(|(_, data)| data).unwrap_or_default(),
+    };
+
+    let connection = client.connection();
+    connection.try_send_message(ClientMessage::AttachProof { claim, header });
+    for chunk in proof.chunks() {
+        connection.try_send_message(ClientMessage::ProofChunk {
+            claim,
+            data: chunk.to_vec(),
+        });
+    }
+    *draft = ProofDraft::default();
+}
+
+/// Editor of the proof attached to the next claim
+pub fn proof_draft_ui(ui: &mut egui::Ui, draft: &mut ProofDraft) {
+    ui.separator();
+    ui.label("Proof");
+    ui.separator();
+    egui::Grid::new("Proof Draft Grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Note");
+            ui.add(egui::TextEdit::singleline(&mut draft.note).char_limit(MAX_PROOF_NOTE_LENGTH));
+            ui.end_row();
+
+            ui.label("Image");
+            ui.text_edit_singleline(&mut draft.image_path)
+                .on_hover_text("Path to a screenshot");
+            ui.end_row();
+        });
+    if !draft.is_empty() {
+        ui.label("Attached to your next claim");
+    }
+}
+
+enum ProofDownload {
+    Requested,
+    Missing,
+    Receiving(Proof),
+}
+
+/// Proofs downloaded for the claim detail panel
+#[derive(Resource, Default)]
+pub struct ProofViewer {
+    /// Field shown in the claim detail panel
+    field: Option<(u8, u8)>,
+    downloads: HashMap<FieldClaim, ProofDownload>,
+    /// Decoded images, `None` if decoding failed
+    textures: HashMap<FieldClaim, Option<(Handle<Image>, egui::TextureId, egui::Vec2)>>,
+}
+
+impl ProofViewer {
+    pub fn open(&mut self, x: u8, y: u8) {
+        self.field = Some((x, y));
+    }
+
+    pub fn receive_header(&mut self, claim: FieldClaim, header: Option<ProofHeader>) {
+        let download = match header {
+            Some(header) => ProofDownload::Receiving(Proof::new(header)),
+            None => ProofDownload::Missing,
+        };
+        self.downloads.insert(claim, download);
+    }
+
+    pub fn receive_chunk(&mut self, claim: FieldClaim, data: &[u8]) {
+        if let Some(ProofDownload::Receiving(proof)) = self.downloads.get_mut(&claim) {
+            if !proof.push_chunk(data) {
+                self.downloads.insert(claim, ProofDownload::Missing);
+            }
+        }
+    }
+
+    /// Decode images of fully downloaded proofs
+    fn prepare_textures(&mut self, images: &mut Assets<Image>, textures: &mut EguiUserTextures) {
+        for (claim, download) in self.downloads.iter() {
+            let ProofDownload::Receiving(proof) = download else {
+                continue;
+            };
+            let Some(image_name) = &proof.header.image_name else {
+                continue;
+            };
+            if !proof.is_complete() || self.textures.contains_key(claim) {
+                continue;
+            }
+            let extension = Path::new(image_name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let image = Image::from_buffer(
+                &proof.image,
+                ImageType::Extension(&extension),
+                CompressedImageFormats::NONE,
+                true,
+                ImageSampler::Default,
+            );
+            let texture = match image {
+                Ok(image) => {
+                    let size = image.size().as_vec2();
+                    let size = egui::vec2(PREVIEW_WIDTH, PREVIEW_WIDTH * size.y / size.x);
+                    let handle = images.add(image);
+                    let id = textures.add_image(handle.clone());
+                    Some((handle, id, size))
+                }
+                Err(e) => {
+                    warn!("Failed to decode proof image due to error {}", e);
+                    None
+                }
+            };
+            self.textures.insert(*claim, texture);
+        }
+    }
+
+    fn close(&mut self, textures: &mut EguiUserTextures) {
+        for (handle, _, _) in self.textures.drain().filter_map(|(_, texture)| texture) {
+            textures.remove_image(&handle);
+        }
+        self.downloads.clear();
+        self.field = None;
+    }
+}
+
+/// Claims of a field with their proofs, for the host and referees
+fn claim_details_ui(
+    mut egui_ctx: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut viewer: ResMut<ProofViewer>,
+    mut images: ResMut<Assets<Image>>,
+    mut textures: ResMut<EguiUserTextures>,
+    board: Res<BoardRes>,
+    client: Res<Client>,
+) {
+    let Ok(mut ctx) = egui_ctx.get_single_mut() else {
+        return;
+    };
+    let Some((x, y)) = viewer.field else {
+        return;
+    };
+    viewer.prepare_textures(&mut images, &mut textures);
+
+    let mut open = true;
+    egui::Window::new("Claim details")
+        .open(&mut open)
+        .collapsible(false)
+        .vscroll(true)
+        .show(ctx.get_mut(), |ui| {
+            ui.label(board.prompt(x, y));
+            for team in Team::iter() {
+                let status = if board.is_active(x, y, team) {
+                    "Claimed"
+                } else if board.is_pending(x, y, team) {
+                    "Pending"
+                } else {
+                    continue;
+                };
+                let claim = FieldClaim { team: *team, x, y };
+                ui.separator();
+                let styles = &board.config.team_styles;
+                ui.label(
+                    egui::RichText::new(format!("{}: {}", styles.name(*team), status))
+                        .color(styles.color(*team)),
+                );
+                match viewer.downloads.get(&claim) {
+                    None => {
+                        if ui.button("Load proof").clicked() {
+                            client
+                                .connection()
+                                .try_send_message(ClientMessage::RequestProof(claim));
+                            viewer.downloads.insert(claim, ProofDownload::Requested);
+                        }
+                    }
+                    Some(ProofDownload::Requested) => {
+                        ui.spinner();
+                    }
+                    Some(ProofDownload::Missing) => {
+                        ui.label("No proof");
+                    }
+                    Some(ProofDownload::Receiving(proof)) => {
+                        if !proof.header.note.is_empty() {
+                            ui.label(&proof.header.note);
+                        }
+                        let Some(image_name) = &proof.header.image_name else {
+                            continue;
+                        };
+                        match viewer.textures.get(&claim) {
+                            Some(Some((_, id, size))) => {
+                                ui.image(egui::load::SizedTexture::new(*id, *size));
+                            }
+                            Some(None) => {
+                                ui.label(format!("Cannot preview {}", image_name));
+                            }
+                            None => {
+                                ui.add(egui::ProgressBar::new(proof.progress()).text(image_name));
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    if !open {
+        viewer.close(&mut textures);
+    }
+}
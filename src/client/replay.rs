@@ -0,0 +1,258 @@
+use std::path::PathBuf;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::EguiContext;
+
+use common::{
+    bingo::format_match_time,
+    replay::{Replay, ReplayState},
+    BoardRes,
+};
+
+use crate::{
+    fit_text::PromptLayoutCache,
+    game_ui::{match_summary_ui, spectated_bingo_field, GAP_SIZE},
+    states::AppState,
+    ui::root_element,
+};
+
+/// Selectable playback speeds
+const SPEEDS: [f32; 5] = [0.5, 1.0, 2.0, 4.0, 8.0];
+
+#[derive(Event)]
+pub struct OpenReplay {
+    pub path: PathBuf,
+}
+
+/// Error of the last replay that failed to open
+#[derive(Resource, Default)]
+pub struct ReplayLoadError(pub Option<String>);
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OpenReplay>()
+            .init_resource::<ReplayLoadError>()
+            .add_systems(Update, open_replay.run_if(in_state(AppState::MainMenu)))
+            .add_systems(OnEnter(AppState::Replay), add_resources)
+            .add_systems(OnExit(AppState::Replay), remove_resources)
+            .add_systems(
+                Update,
+                (advance_replay, replay_menu_ui, replay_board_ui)
+                    .chain()
+                    .run_if(in_state(AppState::Replay)),
+            );
+    }
+}
+
+fn add_resources(mut commands: Commands) {
+    commands.init_resource::<PromptLayoutCache>();
+}
+
+fn remove_resources(mut commands: Commands) {
+    commands.remove_resource::<ReplayPlayer>();
+    commands.remove_resource::<BoardRes>();
+    commands.remove_resource::<PromptLayoutCache>();
+}
+
+/// Playback state of an opened replay
+#[derive(Resource)]
+struct ReplayPlayer {
+    name: String,
+    replay: Replay,
+    /// Playback position in milliseconds
+    time_ms: f64,
+    playing: bool,
+    speed: f32,
+    state: ReplayState,
+    /// Number of entries applied to the state
+    applied: usize,
+}
+
+impl ReplayPlayer {
+    fn new(name: String, replay: Replay) -> Self {
+        let state = replay.initial_state();
+        Self {
+            name,
+            replay,
+            time_ms: 0.0,
+            playing: false,
+            speed: 1.0,
+            state,
+            applied: 0,
+        }
+    }
+
+    /// Bring the state to the playback position, returns whether it changed
+    fn sync(&mut self) -> bool {
+        let time_ms = self.time_ms as u64;
+        let mut changed = false;
+        let last_applied = self
+            .applied
+            .checked_sub(1)
+            .and_then(|i| self.replay.entries.get(i));
+        if last_applied.is_some_and(|entry| entry.time_ms > time_ms) {
+            self.state = self.replay.initial_state();
+            self.applied = 0;
+            changed = true;
+        }
+        while let Some(entry) = self
+            .replay
+            .entries
+            .get(self.applied)
+            .filter(|entry| entry.time_ms <= time_ms)
+        {
+            self.state.apply(entry);
+            self.applied += 1;
+            changed = true;
+        }
+        changed
+    }
+}
+
+fn open_replay(
+    mut commands: Commands,
+    mut events: EventReader<OpenReplay>,
+    mut load_error: ResMut<ReplayLoadError>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    for event in events.read() {
+        let replay = match Replay::load(&event.path) {
+            Ok(replay) => replay,
+            Err(e) => {
+                warn!("Failed to open replay due to error {}", e);
+                load_error.0 = Some(e.to_string());
+                continue;
+            }
+        };
+        let name = event
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let player = ReplayPlayer::new(name, replay);
+        commands.insert_resource(BoardRes {
+            board: player.state.board.clone(),
+            changed: false,
+        });
+        commands.insert_resource(player);
+        load_error.0 = None;
+        state.set(AppState::Replay);
+    }
+}
+
+fn advance_replay(time: Res<Time>, mut player: ResMut<ReplayPlayer>, mut board: ResMut<BoardRes>) {
+    let duration = player.replay.duration_ms() as f64;
+    if player.playing {
+        player.time_ms += time.delta_seconds_f64() * 1000.0 * player.speed as f64;
+        if player.time_ms >= duration {
+            player.time_ms = duration;
+            player.playing = false;
+        }
+    }
+    if player.sync() {
+        board.board = player.state.board.clone();
+    }
+}
+
+fn replay_menu_ui(
+    mut egui_ctx: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut player: ResMut<ReplayPlayer>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    let Ok(mut ctx) = egui_ctx.get_single_mut() else {
+        return;
+    };
+    let duration = player.replay.duration_ms() as f64;
+
+    root_element(ctx.get_mut(), |ui| {
+        ui.separator();
+        ui.label(format!("Replay {}", player.name));
+        ui.separator();
+
+        ui.label(format!(
+            "{} / {}",
+            format_match_time(player.time_ms as u64 / 1000),
+            format_match_time(duration as u64 / 1000)
+        ));
+        ui.add(
+            egui::Slider::new(&mut player.time_ms, 0.0..=duration)
+                .show_value(false)
+                .text("Position"),
+        );
+        ui.horizontal(|ui| {
+            let label = match player.playing {
+                true => "Pause",
+                false => "Play",
+            };
+            if ui.button(label).clicked() {
+                if !player.playing && player.time_ms >= duration {
+                    player.time_ms = 0.0;
+                }
+                player.playing = !player.playing;
+            }
+            if ui.button("Restart").clicked() {
+                player.time_ms = 0.0;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Speed");
+            for speed in SPEEDS {
+                ui.selectable_value(&mut player.speed, speed, format!("{}x", speed));
+            }
+        });
+
+        ui.separator();
+        ui.label("Users");
+        ui.separator();
+        let styles = &player.state.board.config.team_styles;
+        egui::Grid::new("Replay Users Grid").show(ui, |ui| {
+            for client_props in player.state.clients.values() {
+                let mut username = egui::RichText::new(&client_props.username);
+                if let Some(team) = client_props.team {
+                    username = username.color(styles.color(team));
+                }
+                ui.label(username);
+                ui.end_row();
+            }
+        });
+
+        if let Some(team) = player.state.winner {
+            ui.label(
+                egui::RichText::new(format!("{} won", styles.name(team))).color(styles.color(team)),
+            );
+        }
+        match_summary_ui(ui, &player.state.board);
+
+        ui.separator();
+        if ui.button("Close replay").clicked() {
+            state.set(AppState::MainMenu);
+        }
+    });
+}
+
+fn replay_board_ui(
+    mut egui_ctx: Query<&mut EguiContext, Without<PrimaryWindow>>,
+    board: Res<BoardRes>,
+    mut prompt_layout_cache: ResMut<PromptLayoutCache>,
+) {
+    let Ok(mut ctx) = egui_ctx.get_single_mut() else {
+        return;
+    };
+
+    egui::CentralPanel::default()
+        .frame(egui::Frame::none().inner_margin(GAP_SIZE))
+        .show(ctx.get_mut(), |ui| {
+            egui::Grid::new("Replay Bingo Grid")
+                .spacing((GAP_SIZE, GAP_SIZE))
+                .show(ui, |ui| {
+                    for y in 0..board.config.prompts.y_size {
+                        for x in 0..board.config.prompts.x_size {
+                            spectated_bingo_field(ui, &board, (x, y), &mut prompt_layout_cache);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
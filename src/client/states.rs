@@ -15,4 +15,6 @@ pub enum AppState {
     #[default]
     MainMenu,
     Playing,
+    /// Playback of a recorded match, without a server
+    Replay,
 }
@@ -11,7 +11,8 @@ pub struct UserdataPlugin;
 impl Plugin for UserdataPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::MainMenu), insert_userdata)
-            .add_systems(OnExit(AppState::Playing), remove_userdata);
+            .add_systems(OnExit(AppState::Playing), remove_userdata)
+            .add_systems(OnExit(AppState::Replay), remove_userdata);
     }
 }
 
@@ -27,6 +28,15 @@ fn remove_userdata(mut commands: Commands) {
 pub struct Userdata {
     pub username: String,
     pub addr: String,
+    #[serde(default)]
+    pub replay_path: String,
+    /// Whether the passwords below are saved
+    #[serde(default)]
+    pub remember_passwords: bool,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub room_password: String,
 }
 
 impl StoragePath for Userdata {
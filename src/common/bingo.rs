@@ -1,8 +1,14 @@
-use std::{collections::HashSet, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy_quinnet::shared::ClientId;
 
 use serde::{Deserialize, Serialize};
 
-use super::teams::Team;
+use super::teams::{Team, TeamSettings, TeamStyles};
 
 /// Bingo game mode
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
@@ -11,6 +17,52 @@ pub enum GameMode {
     Lockout,
     /// Every team can claim every field
     FFA,
+    /// Only one team can claim a field, but other teams can steal it
+    Steal,
+    /// Only one team can claim a field, teams expand from their starting edge
+    Invasion,
+}
+
+impl GameMode {
+    /// Whether a field can be claimed by at most one team
+    pub fn is_exclusive(&self) -> bool {
+        matches!(
+            self,
+            GameMode::Lockout | GameMode::Steal | GameMode::Invasion
+        )
+    }
+}
+
+/// Side of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Edge {
+    /// Starting edge of a team in invasion mode, consecutive teams start on opposite sides
+    pub fn start_of(team: &Team) -> Self {
+        [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right][team.index() % 4]
+    }
+
+    /// Whether the field lies on this edge of the board
+    pub fn contains(&self, prompts: &BoardPrompts, x: u8, y: u8) -> bool {
+        match self {
+            Edge::Top => y == 0,
+            Edge::Bottom => y + 1 == prompts.y_size,
+            Edge::Left => x == 0,
+            Edge::Right => x + 1 == prompts.x_size,
+        }
+    }
+}
+
+impl Display for Edge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{:?}", self))
+    }
 }
 
 impl Display for GameMode {
@@ -19,11 +71,57 @@ impl Display for GameMode {
     }
 }
 
+/// Restrictions of stealing fields in steal mode
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StealRules {
+    /// Players have to confirm they completed the prompt again.
+    /// Only the client asks for the confirmation, the server can't verify it.
+    pub require_recompletion: bool,
+    /// Seconds after a claim during which the field cannot be stolen
+    pub cooldown_secs: u16,
+    /// Maximum number of steals of each team
+    pub max_steals: Option<u8>,
+}
+
+impl Default for StealRules {
+    fn default() -> Self {
+        Self {
+            require_recompletion: true,
+            cooldown_secs: 30,
+            max_steals: None,
+        }
+    }
+}
+
+impl Display for StealRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}s cooldown", self.cooldown_secs))?;
+        if let Some(max_steals) = self.max_steals {
+            f.write_fmt(format_args!(", {} steals per team", max_steals))?;
+        }
+        if self.require_recompletion {
+            f.write_str(", redo prompt")?;
+        }
+        Ok(())
+    }
+}
+
 /// Combined game mode and win condition
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct BoardMode {
     pub game_mode: GameMode,
     pub win_condition: WinCondition,
+    /// Per-team win conditions used instead of the shared one
+    #[serde(default)]
+    pub team_win_conditions: HashMap<Team, WinCondition>,
+    #[serde(default)]
+    pub steal_rules: StealRules,
+    /// Seconds a team has to wait between claims
+    #[serde(default)]
+    pub claim_cooldown_secs: u16,
+    /// Claims only count after a referee approves them
+    #[serde(default)]
+    pub require_approval: bool,
 }
 
 impl Default for BoardMode {
@@ -31,16 +129,55 @@ impl Default for BoardMode {
         Self {
             game_mode: GameMode::FFA,
             win_condition: WinCondition::InRow { length: 5, rows: 1 },
+            team_win_conditions: HashMap::new(),
+            steal_rules: StealRules::default(),
+            claim_cooldown_secs: 0,
+            require_approval: false,
         }
     }
 }
 
-/// Size and prompts of the board
+impl BoardMode {
+    /// Win condition of a team, including its handicap
+    pub fn win_condition_for(&self, team: &Team) -> WinCondition {
+        self.team_win_conditions
+            .get(team)
+            .copied()
+            .unwrap_or(self.win_condition)
+    }
+}
+
+/// Special rule of a single field
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldKind {
+    /// Claimed by playing
+    #[default]
+    Normal,
+    /// Counts as claimed by every team
+    Free,
+    /// Counts as claimed by the team from the start
+    PreClaimed(Team),
+}
+
+impl Display for FieldKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldKind::Normal => f.write_str("Normal"),
+            FieldKind::Free => f.write_str("Free"),
+            FieldKind::PreClaimed(team) => f.write_fmt(format_args!("{} claimed", team)),
+        }
+    }
+}
+
+/// Size, prompts and special fields of the board
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct BoardPrompts {
     pub x_size: u8,
     pub y_size: u8,
     pub prompts: Vec<String>,
+    /// Missing entries are normal fields
+    #[serde(default)]
+    pub fields: Vec<FieldKind>,
 }
 
 impl Default for BoardPrompts {
@@ -49,11 +186,23 @@ impl Default for BoardPrompts {
             x_size: 5,
             y_size: 5,
             prompts: vec![String::new(); 25],
+            fields: vec![FieldKind::Normal; 25],
         }
     }
 }
 
 impl BoardPrompts {
+    /// Whether there is a prompt for every field and no more
+    pub fn is_valid(&self) -> bool {
+        let size = self.x_size as usize * self.y_size as usize;
+        self.prompts.len() == size && self.fields.len() <= size
+    }
+
+    /// Whether the field lies on the board
+    pub fn contains(&self, x: u8, y: u8) -> bool {
+        x < self.x_size && y < self.y_size
+    }
+
     /// Calculate offset to access a specific field in the prompt vector
     pub fn offset(&self, x: u8, y: u8) -> usize {
         x as usize * self.y_size as usize + y as usize
@@ -64,12 +213,95 @@ impl BoardPrompts {
         let offset = self.offset(x, y);
         &self.prompts[offset]
     }
+
+    /// Get a mutable access to a prompt
+    pub fn prompt_mut(&mut self, x: u8, y: u8) -> &mut String {
+        let offset = self.offset(x, y);
+        &mut self.prompts[offset]
+    }
+
+    /// Get the special rule of a field
+    pub fn field(&self, x: u8, y: u8) -> FieldKind {
+        let offset = self.offset(x, y);
+        self.fields.get(offset).copied().unwrap_or_default()
+    }
+
+    /// Set the special rule of a field
+    pub fn set_field(&mut self, x: u8, y: u8, kind: FieldKind) {
+        let offset = self.offset(x, y);
+        if self.fields.len() <= offset {
+            self.fields.resize(offset + 1, FieldKind::Normal);
+        }
+        self.fields[offset] = kind;
+    }
+}
+
+/// Claim of a field by a team
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct FieldClaim {
+    pub team: Team,
+    pub x: u8,
+    pub y: u8,
+}
+
+/// Player who made a claim and when
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClaimAttribution {
+    pub claim: FieldClaim,
+    pub client_id: ClientId,
+    pub username: String,
+    /// Server time in seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Current time in seconds since the Unix epoch
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Current time in milliseconds since the Unix epoch
+pub fn unix_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Format seconds since the start of a match
+pub fn format_match_time(secs: u64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    match hours {
+        0 => format!("{:02}:{:02}", minutes, secs),
+        _ => format!("{}:{:02}:{:02}", hours, minutes, secs),
+    }
 }
 
 /// Activity of all teams on the board
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoardActivity {
     pub activity: Vec<HashSet<Team>>,
+    /// Previous owners of stolen fields, oldest first
+    #[serde(default)]
+    pub history: Vec<Vec<Team>>,
+    /// Number of fields stolen by each team
+    #[serde(default)]
+    pub steal_counts: HashMap<Team, u8>,
+    /// Claims waiting for a referee, oldest first
+    #[serde(default)]
+    pub pending: Vec<FieldClaim>,
+    /// Players behind current and pending claims
+    #[serde(default)]
+    pub attributions: Vec<ClaimAttribution>,
+    /// Server time of the start of the match, in seconds since the Unix epoch
+    #[serde(default)]
+    pub started_at: u64,
+    /// Server time of the latest claim of each field, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub claimed_at: Vec<Option<u64>>,
+    /// Server time each team can claim again, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub claim_cooldowns: HashMap<Team, u64>,
 }
 
 impl BoardActivity {
@@ -77,7 +309,52 @@ impl BoardActivity {
     pub fn empty(size: usize) -> Self {
         Self {
             activity: vec![HashSet::new(); size],
+            history: vec![Vec::new(); size],
+            steal_counts: HashMap::new(),
+            pending: Vec::new(),
+            attributions: Vec::new(),
+            started_at: unix_now(),
+            claimed_at: vec![None; size],
+            claim_cooldowns: HashMap::new(),
+        }
+    }
+
+    /// Previous owners of a field, oldest first
+    pub fn history(&self, offset: usize) -> &[Team] {
+        self.history.get(offset).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn steal_count(&self, team: &Team) -> u8 {
+        self.steal_counts.get(team).copied().unwrap_or(0)
+    }
+
+    pub fn attribution(&self, claim: &FieldClaim) -> Option<&ClaimAttribution> {
+        self.attributions.iter().find(|a| a.claim == *claim)
+    }
+
+    /// Record who made a claim, replacing the previous record
+    pub fn attribute(&mut self, attribution: ClaimAttribution) {
+        self.forget_attribution(&attribution.claim);
+        self.attributions.push(attribution);
+    }
+
+    fn forget_attribution(&mut self, claim: &FieldClaim) {
+        self.attributions.retain(|a| a.claim != *claim);
+    }
+
+    fn mark_claimed(&mut self, offset: usize) {
+        if self.claimed_at.len() <= offset {
+            self.claimed_at.resize(offset + 1, None);
         }
+        self.claimed_at[offset] = Some(unix_now_ms());
+    }
+
+    fn record_steal(&mut self, offset: usize, thief: Team, victim: Team) {
+        if self.history.len() <= offset {
+            self.history.resize(offset + 1, Vec::new());
+        }
+        self.history[offset].push(victim);
+        *self.steal_counts.entry(thief).or_default() += 1;
     }
 }
 
@@ -87,6 +364,10 @@ impl BoardActivity {
 pub struct BoardConfig {
     pub mode: BoardMode,
     pub prompts: BoardPrompts,
+    #[serde(default)]
+    pub teams: TeamSettings,
+    #[serde(default)]
+    pub team_styles: TeamStyles,
 }
 
 
@@ -116,6 +397,17 @@ impl Board {
         &self.activity.activity[offset]
     }
 
+    /// Teams that get a marker slot, the active ones plus removed ones that still have claims
+    pub fn shown_teams(&self) -> impl Iterator<Item = Team> {
+        let claimed = self.activity.activity.iter().flatten();
+        let pending = self.activity.pending.iter().map(|claim| &claim.team);
+        let last_claimed = claimed.chain(pending).map(|team| team.index() + 1).max();
+        let count = last_claimed
+            .unwrap_or(0)
+            .max(self.config.teams.count as usize);
+        Team::iter().take(count).copied()
+    }
+
     pub fn is_active(&self, x: u8, y: u8, team: &Team) -> bool {
         let offset = self.offset(x, y);
         self.activity.activity[offset].contains(team)
@@ -126,121 +418,284 @@ impl Board {
         &mut self.activity.activity[offset]
     }
 
+    pub fn field(&self, x: u8, y: u8) -> FieldKind {
+        self.config.prompts.field(x, y)
+    }
+
+    /// Whether the field counts as claimed by the team in win checks
+    pub fn is_claimed(&self, x: u8, y: u8, team: &Team) -> bool {
+        match self.field(x, y) {
+            FieldKind::Normal => self.is_active(x, y, team),
+            FieldKind::Free => true,
+            FieldKind::PreClaimed(owner) => owner == *team,
+        }
+    }
+
+    /// Whether the team could claim the field right now
+    pub fn can_claim(&self, x: u8, y: u8, team: &Team) -> bool {
+        if self.field(x, y) != FieldKind::Normal || self.check_win().is_some() {
+            return false;
+        }
+        if self.config.mode.game_mode == GameMode::Invasion && !self.is_reachable(x, y, team) {
+            return false;
+        }
+        !self.config.mode.game_mode.is_exclusive() || self.activity(x, y).is_empty()
+    }
+
+    /// Whether the field lies on the team's starting edge or next to a field it claimed
+    pub fn is_reachable(&self, x: u8, y: u8, team: &Team) -> bool {
+        if Edge::start_of(team).contains(&self.config.prompts, x, y) {
+            return true;
+        }
+        let (x_size, y_size) = (self.config.prompts.x_size, self.config.prompts.y_size);
+        let neighbours = [
+            x.checked_sub(1).map(|x| (x, y)),
+            (x + 1 < x_size).then_some((x + 1, y)),
+            y.checked_sub(1).map(|y| (x, y)),
+            (y + 1 < y_size).then_some((x, y + 1)),
+        ];
+        neighbours
+            .into_iter()
+            .flatten()
+            .any(|(x, y)| self.is_claimed(x, y, team))
+    }
+
+    /// Whether the team could steal the field from its owner right now
+    pub fn can_steal(&self, x: u8, y: u8, team: &Team) -> bool {
+        if self.config.mode.game_mode != GameMode::Steal
+            || self.field(x, y) != FieldKind::Normal
+            || self.check_win().is_some()
+        {
+            return false;
+        }
+        let Some(owner) = self.activity(x, y).iter().next() else {
+            return false;
+        };
+        if owner == team {
+            return false;
+        }
+        let rules = &self.config.mode.steal_rules;
+        if rules
+            .max_steals
+            .is_some_and(|max_steals| self.activity.steal_count(team) >= max_steals)
+        {
+            return false;
+        }
+        let cooldown_ms = rules.cooldown_secs as u64 * 1000;
+        let offset = self.offset(x, y);
+        !matches!(
+            self.activity.claimed_at.get(offset),
+            Some(Some(claimed_at)) if unix_now_ms() < claimed_at + cooldown_ms
+        )
+    }
+
+    /// Time left until the team can claim another field
+    pub fn claim_cooldown_left(&self, team: &Team) -> Option<Duration> {
+        let ends_at = *self.activity.claim_cooldowns.get(team)?;
+        let left = ends_at.checked_sub(unix_now_ms())?;
+        (left > 0).then(|| Duration::from_millis(left))
+    }
+
+    pub fn is_pending(&self, x: u8, y: u8, team: &Team) -> bool {
+        self.activity
+            .pending
+            .contains(&FieldClaim { team: *team, x, y })
+    }
+
+    /// Claim the field for the team, stealing it if possible.
+    /// When referee approval is required, the claim is queued instead.
+    /// Returns whether the board changed.
+    pub fn claim(&mut self, x: u8, y: u8, team: Team) -> bool {
+        if self.claim_cooldown_left(&team).is_some() {
+            return false;
+        }
+        let claimed = if self.config.mode.require_approval {
+            let claimable = self.can_claim(x, y, &team) || self.can_steal(x, y, &team);
+            if claimable && !self.is_pending(x, y, &team) {
+                self.activity.pending.push(FieldClaim { team, x, y });
+                true
+            } else {
+                false
+            }
+        } else {
+            self.apply_claim(x, y, team)
+        };
+        let cooldown_ms = self.config.mode.claim_cooldown_secs as u64 * 1000;
+        if claimed && cooldown_ms > 0 {
+            let ends_at = unix_now_ms() + cooldown_ms;
+            self.activity.claim_cooldowns.insert(team, ends_at);
+        }
+        claimed
+    }
+
+    /// Approve or reject a queued claim, returns whether the board changed
+    pub fn review_claim(&mut self, claim: FieldClaim, approve: bool) -> bool {
+        let Some(index) = self.activity.pending.iter().position(|c| *c == claim) else {
+            return false;
+        };
+        self.activity.pending.remove(index);
+        if !approve || !self.apply_claim(claim.x, claim.y, claim.team) {
+            self.activity.forget_attribution(&claim);
+        }
+        true
+    }
+
+    fn apply_claim(&mut self, x: u8, y: u8, team: Team) -> bool {
+        let offset = self.offset(x, y);
+        if self.can_claim(x, y, &team) {
+            self.activity.activity[offset].insert(team);
+        } else if self.can_steal(x, y, &team) {
+            let activity = &mut self.activity.activity[offset];
+            let victim = *activity.iter().next().unwrap();
+            activity.clear();
+            activity.insert(team);
+            self.activity.record_steal(offset, team, victim);
+            self.activity
+                .forget_attribution(&FieldClaim { team: victim, x, y });
+        } else {
+            return false;
+        }
+        self.activity.mark_claimed(offset);
+        true
+    }
+
+    /// Remove the claim of the team, pending or not, returns whether the board changed
+    pub fn unclaim(&mut self, x: u8, y: u8, team: &Team) -> bool {
+        let pending_count = self.activity.pending.len();
+        self.activity
+            .pending
+            .retain(|c| *c != FieldClaim { team: *team, x, y });
+        let was_pending = pending_count != self.activity.pending.len();
+        let was_active = self.activity_mut(x, y).remove(team);
+        self.activity
+            .forget_attribution(&FieldClaim { team: *team, x, y });
+        was_pending || was_active
+    }
+
     pub fn check_win(&self) -> Option<Team> {
-        match self.config.mode.win_condition {
-            WinCondition::InRow { length, rows } => self.check_win_in_row(length, rows),
-            WinCondition::Domination => self.check_win_domination(),
-            WinCondition::FirstTo(n) => self.check_win_first_to(n),
-        }
-    }
-
-    fn check_win_in_row(&self, length: u8, rows: u8) -> Option<Team> {
-        for team in Team::iter() {
-            let mut winning_rows = 0;
-            let x_size = self.config.prompts.x_size;
-            let y_size = self.config.prompts.y_size;
-            // L-R
-            if length <= y_size {
-                for sx in 0..x_size {
-                    'xy: for sy in 0..y_size + 1 - length {
-                        for d in 0..length {
-                            if !self.is_active(sx, sy + d, team) {
-                                continue 'xy;
-                            }
-                        }
-                        winning_rows += 1;
-                    }
+        Team::iter().find(|team| self.has_won(team)).copied()
+    }
+
+    /// Whether the team fulfills its own win condition
+    pub fn has_won(&self, team: &Team) -> bool {
+        match self.config.mode.win_condition_for(team) {
+            WinCondition::InRow { length, rows } => self.check_win_in_row(team, length, rows),
+            WinCondition::Domination => self.check_win_domination(team),
+            WinCondition::FirstTo(n) => self.check_win_first_to(team, n),
+        }
+    }
+
+    /// Every straight line of the given length, horizontal, vertical and diagonal
+    fn lines(&self, length: u8) -> Vec<Vec<(u8, u8)>> {
+        let mut lines = Vec::new();
+        let x_size = self.config.prompts.x_size;
+        let y_size = self.config.prompts.y_size;
+        // L-R
+        if length <= y_size {
+            for sx in 0..x_size {
+                for sy in 0..y_size + 1 - length {
+                    lines.push((0..length).map(|d| (sx, sy + d)).collect());
                 }
             }
-            // T-D
-            if length <= x_size {
-                for sx in 0..x_size + 1 - length {
-                    'xy: for sy in 0..y_size {
-                        for d in 0..length {
-                            if !self.is_active(sx + d, sy, team) {
-                                continue 'xy;
-                            }
-                        }
-                        winning_rows += 1;
-                    }
+        }
+        // T-D
+        if length <= x_size {
+            for sx in 0..x_size + 1 - length {
+                for sy in 0..y_size {
+                    lines.push((0..length).map(|d| (sx + d, sy)).collect());
                 }
             }
-            // TL-BR
-            if length <= x_size && length <= y_size {
-                for sx in 0..x_size + 1 - length {
-                    'xy: for sy in 0..y_size + 1 - length {
-                        for d in 0..length {
-                            if !self.is_active(sx + d, sy + d, team) {
-                                continue 'xy;
-                            }
-                        }
-                        winning_rows += 1;
-                    }
+        }
+        // TL-BR
+        if length <= x_size && length <= y_size {
+            for sx in 0..x_size + 1 - length {
+                for sy in 0..y_size + 1 - length {
+                    lines.push((0..length).map(|d| (sx + d, sy + d)).collect());
                 }
             }
-            // BL-TR
-            if length <= x_size && length <= y_size {
-                for sx in 0..x_size + 1 - length {
-                    'xy: for sy in 0..y_size + 1 - length {
-                        for d in 0..length {
-                            if !self.is_active(sx + d, y_size - sy - d - 1, team) {
-                                continue 'xy;
-                            }
-                        }
-                        winning_rows += 1;
-                    }
+        }
+        // BL-TR
+        if length <= x_size && length <= y_size {
+            for sx in 0..x_size + 1 - length {
+                for sy in 0..y_size + 1 - length {
+                    lines.push((0..length).map(|d| (sx + d, y_size - sy - d - 1)).collect());
                 }
             }
-
-            if winning_rows >= rows {
-                return Some(*team);
-            }
         }
-        None
+        lines
+    }
+
+    /// Lines of the given length fully claimed by the team
+    fn claimed_lines(&self, team: &Team, length: u8) -> Vec<Vec<(u8, u8)>> {
+        self.lines(length)
+            .into_iter()
+            .filter(|line| line.iter().all(|(x, y)| self.is_claimed(*x, *y, team)))
+            .collect()
+    }
+
+    fn check_win_in_row(&self, team: &Team, length: u8, rows: u8) -> bool {
+        self.claimed_lines(team, length).len() >= rows as usize
     }
 
-    fn check_win_first_to(&self, n: u8) -> Option<Team> {
-        for team in Team::iter() {
-            let mut count = 0;
-            for x in 0..self.config.prompts.x_size {
-                for y in 0..self.config.prompts.y_size {
-                    if self.is_active(x, y, team) {
-                        count += 1;
+    /// Fields making up the team's win, completed lines for row conditions and all claims otherwise
+    pub fn winning_fields(&self, team: &Team) -> HashSet<(u8, u8)> {
+        match self.config.mode.win_condition_for(team) {
+            WinCondition::InRow { length, .. } => self
+                .claimed_lines(team, length)
+                .into_iter()
+                .flatten()
+                .collect(),
+            WinCondition::Domination | WinCondition::FirstTo(_) => {
+                let mut fields = HashSet::new();
+                for x in 0..self.config.prompts.x_size {
+                    for y in 0..self.config.prompts.y_size {
+                        if self.is_claimed(x, y, team) {
+                            fields.insert((x, y));
+                        }
                     }
                 }
+                fields
             }
-            if count >= n {
-                return Some(*team);
+        }
+    }
+
+    /// Number of fields counted as claimed by the team
+    pub fn claimed_count(&self, team: &Team) -> u32 {
+        let mut count = 0;
+        for x in 0..self.config.prompts.x_size {
+            for y in 0..self.config.prompts.y_size {
+                if self.is_claimed(x, y, team) {
+                    count += 1;
+                }
             }
         }
-        None
+        count
     }
 
-    fn check_win_domination(&self) -> Option<Team> {
-        if self.config.mode.game_mode != GameMode::Lockout {
-            return None;
+    fn check_win_first_to(&self, team: &Team, n: u8) -> bool {
+        self.claimed_count(team) >= n as u32
+    }
+
+    fn check_win_domination(&self, team: &Team) -> bool {
+        if !self.config.mode.game_mode.is_exclusive() {
+            return false;
         }
-        let mut total_count = 0;
-        let mut team_counts = Vec::<(Team, u32)>::new();
-        for team in Team::iter() {
-            let mut count = 0;
-            for x in 0..self.config.prompts.x_size {
-                for y in 0..self.config.prompts.y_size {
-                    if self.is_active(x, y, team) {
-                        count += 1;
-                    }
+        // Free fields count for every team, so only unclaimed normal fields can change the outcome
+        let mut free_space = 0;
+        for x in 0..self.config.prompts.x_size {
+            for y in 0..self.config.prompts.y_size {
+                if self.field(x, y) == FieldKind::Normal && self.activity(x, y).is_empty() {
+                    free_space += 1;
                 }
             }
-            team_counts.push((*team, count));
-            total_count += count;
-        }
-        let free_space =
-            self.config.prompts.x_size as u32 * self.config.prompts.y_size as u32 - total_count;
-        team_counts.sort_by(|a, b| b.1.cmp(&a.1));
-        if free_space + team_counts[1].1 < team_counts[0].1 {
-            return Some(team_counts[0].0);
         }
-        None
+        let count = self.claimed_count(team);
+        let best_other = Team::iter()
+            .filter(|other| *other != team)
+            .map(|other| self.claimed_count(other))
+            .max()
+            .unwrap_or(0);
+        free_space + best_other < count
     }
 }
 
@@ -0,0 +1,467 @@
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use super::{
+    bingo::{unix_now, Board, FieldKind},
+    teams::Team,
+};
+
+/// Directory exports are written to by default
+pub const EXPORT_DIR: &str = "exports";
+
+const FIELD_SIZE: u32 = 120;
+const GAP_SIZE: u32 = 4;
+const HEADER_SIZE: u32 = 36;
+const MARKER_SIZE: u32 = 12;
+/// Markers in a row at the bottom of a field, further teams start a row above
+const MARKER_COLUMNS: u32 = 8;
+const EMPTY_COLOR: &str = "#3c3c3c";
+const FREE_COLOR: &str = "#d4af37";
+/// Longest prompt line before wrapping, in characters
+const LINE_LENGTH: usize = 16;
+const MAX_LINES: usize = 6;
+
+/// File format of a board export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Board image with prompts and claims
+    Svg,
+    /// Standalone page with the board image and team results
+    Html,
+    /// One row per field
+    Csv,
+    /// Grid of fields
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Svg,
+        ExportFormat::Html,
+        ExportFormat::Csv,
+        ExportFormat::Json,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "svg",
+            ExportFormat::Html => "html",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    /// Find a format by its extension
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Self::ALL.into_iter().find(|f| f.extension() == name)
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.extension().to_uppercase())
+    }
+}
+
+/// Teams with a claim on the field, without free and pre-claimed fields
+fn active_teams(board: &Board, x: u8, y: u8) -> Vec<Team> {
+    Team::iter()
+        .filter(|team| board.is_active(x, y, team))
+        .copied()
+        .collect()
+}
+
+fn pending_teams(board: &Board, x: u8, y: u8) -> Vec<Team> {
+    Team::iter()
+        .filter(|team| board.is_pending(x, y, team))
+        .copied()
+        .collect()
+}
+
+fn winning_fields(board: &Board) -> (Option<Team>, HashSet<(u8, u8)>) {
+    let winner = board.check_win();
+    let fields = winner.map_or_else(HashSet::new, |team| board.winning_fields(&team));
+    (winner, fields)
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Split a prompt into lines fitting a field, the last line is cut if it doesn't fit
+fn wrap_prompt(prompt: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in prompt.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= LINE_LENGTH => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_owned()),
+        }
+    }
+    if lines.len() > MAX_LINES {
+        lines.truncate(MAX_LINES);
+        if let Some(last) = lines.last_mut() {
+            last.push('…');
+        }
+    }
+    lines
+}
+
+fn field_color(board: &Board, x: u8, y: u8) -> String {
+    match board.field(x, y) {
+        FieldKind::Free => FREE_COLOR.to_owned(),
+        FieldKind::PreClaimed(team) => board.config.team_styles.hex_color(team),
+        FieldKind::Normal => {
+            let owner = active_teams(board, x, y).into_iter().next();
+            match owner {
+                Some(team) if board.config.mode.game_mode.is_exclusive() => {
+                    board.config.team_styles.hex_color(team)
+                }
+                _ => EMPTY_COLOR.to_owned(),
+            }
+        }
+    }
+}
+
+/// Render the board as an SVG image, the winning fields are outlined in the winner's color
+pub fn to_svg(board: &Board) -> String {
+    let prompts = &board.config.prompts;
+    let width = prompts.x_size as u32 * (FIELD_SIZE + GAP_SIZE) + GAP_SIZE;
+    let height = HEADER_SIZE + prompts.y_size as u32 * (FIELD_SIZE + GAP_SIZE) + GAP_SIZE;
+    let (winner, winning) = winning_fields(board);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="sans-serif">"#,
+        width, height
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect width="100%" height="100%" fill="#1b1b1b"/>"##
+    );
+    let title = match winner {
+        Some(team) => format!(
+            "{} - {} won",
+            board.config.mode.game_mode,
+            board.config.team_styles.name(team)
+        ),
+        None => board.config.mode.game_mode.to_string(),
+    };
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" font-size="20" fill="white">{}</text>"#,
+        GAP_SIZE * 2,
+        HEADER_SIZE - 10,
+        escape_xml(&title)
+    );
+
+    for y in 0..prompts.y_size {
+        for x in 0..prompts.x_size {
+            let left = GAP_SIZE + x as u32 * (FIELD_SIZE + GAP_SIZE);
+            let top = HEADER_SIZE + GAP_SIZE + y as u32 * (FIELD_SIZE + GAP_SIZE);
+            let _ = write!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}""#,
+                left,
+                top,
+                FIELD_SIZE,
+                field_color(board, x, y)
+            );
+            match winner {
+                Some(team) if winning.contains(&(x, y)) => {
+                    let color = board.config.team_styles.hex_color(team);
+                    let _ = writeln!(svg, r#" stroke="{}" stroke-width="4"/>"#, color);
+                }
+                _ => svg.push_str("/>\n"),
+            }
+
+            let lines = wrap_prompt(board.prompt(x, y));
+            let line_height = 16;
+            let center_x = left + FIELD_SIZE / 2;
+            let first_y =
+                top + FIELD_SIZE / 2 + 5 - (lines.len() as u32 * line_height) / 2 + line_height / 2;
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="13" fill="white" text-anchor="middle" stroke="black" stroke-width="0.5">"#,
+                center_x, first_y
+            );
+            for (i, line) in lines.iter().enumerate() {
+                let dy = if i == 0 { 0 } else { line_height };
+                let _ = write!(
+                    svg,
+                    r#"<tspan x="{}" dy="{}">{}</tspan>"#,
+                    center_x,
+                    dy,
+                    escape_xml(line)
+                );
+            }
+            svg.push_str("</text>\n");
+
+            let active = active_teams(board, x, y);
+            let pending = pending_teams(board, x, y);
+            for (i, team) in board.shown_teams().enumerate() {
+                let (column, row) = (i as u32 % MARKER_COLUMNS, i as u32 / MARKER_COLUMNS);
+                let marker_x = left + 2 + column * (MARKER_SIZE + 2);
+                let marker_y = top + FIELD_SIZE - (row + 1) * (MARKER_SIZE + 2);
+                if active.contains(&team) {
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}"/>"#,
+                        marker_x,
+                        marker_y,
+                        MARKER_SIZE,
+                        board.config.team_styles.hex_color(team)
+                    );
+                } else if pending.contains(&team) {
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="none" stroke="{3}" stroke-width="2"/>"#,
+                        marker_x + 1,
+                        marker_y + 1,
+                        MARKER_SIZE - 2,
+                        board.config.team_styles.hex_color(team)
+                    );
+                }
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render a standalone page with the board image and the number of claims of each team
+pub fn to_html(board: &Board) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Bingo board</title>\n");
+    html.push_str(
+        "<style>body { background: #1b1b1b; color: white; font-family: sans-serif; } \
+         td { padding: 2px 12px 2px 0; }</style>\n",
+    );
+    html.push_str("</head>\n<body>\n");
+    let _ = writeln!(
+        html,
+        "<p>{}, {}</p>",
+        board.config.mode.game_mode,
+        escape_xml(&board.config.mode.win_condition.to_string())
+    );
+    html.push_str(&to_svg(board));
+    html.push_str("<table>\n");
+    let winner = board.check_win();
+    for team in Team::iter() {
+        let claims = board.claimed_count(team);
+        if claims == 0 {
+            continue;
+        }
+        let result = if winner == Some(*team) { "winner" } else { "" };
+        let _ = writeln!(
+            html,
+            r#"<tr><td style="color: {}">{}</td><td>{} fields</td><td>{}</td></tr>"#,
+            board.config.team_styles.hex_color(*team),
+            escape_xml(&board.config.team_styles.name(*team)),
+            claims,
+            result
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn team_list(board: &Board, teams: &[Team]) -> String {
+    let names = teams
+        .iter()
+        .map(|team| board.config.team_styles.name(*team))
+        .collect::<Vec<_>>()
+        .join(";");
+    escape_csv(&names)
+}
+
+/// Export every field as a row, teams are separated by semicolons
+pub fn to_csv(board: &Board) -> String {
+    let (_, winning) = winning_fields(board);
+    let mut csv = String::from("x,y,prompt,field,claimed_by,pending,winning\n");
+    for y in 0..board.config.prompts.y_size {
+        for x in 0..board.config.prompts.x_size {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{}",
+                x,
+                y,
+                escape_csv(board.prompt(x, y)),
+                escape_csv(&board.field(x, y).to_string()),
+                team_list(board, &active_teams(board, x, y)),
+                team_list(board, &pending_teams(board, x, y)),
+                winning.contains(&(x, y))
+            );
+        }
+    }
+    csv
+}
+
+#[derive(Serialize)]
+struct ExportedField<'a> {
+    prompt: &'a str,
+    kind: FieldKind,
+    claimed_by: Vec<Team>,
+    pending: Vec<Team>,
+    winning: bool,
+}
+
+#[derive(Serialize)]
+struct ExportedBoard<'a> {
+    x_size: u8,
+    y_size: u8,
+    game_mode: String,
+    winner: Option<Team>,
+    /// Rows of fields, top to bottom
+    rows: Vec<Vec<ExportedField<'a>>>,
+}
+
+/// Export the grid of fields as JSON
+pub fn to_json(board: &Board) -> String {
+    let (winner, winning) = winning_fields(board);
+    let prompts = &board.config.prompts;
+    let rows = (0..prompts.y_size)
+        .map(|y| {
+            (0..prompts.x_size)
+                .map(|x| ExportedField {
+                    prompt: board.prompt(x, y),
+                    kind: board.field(x, y),
+                    claimed_by: active_teams(board, x, y),
+                    pending: pending_teams(board, x, y),
+                    winning: winning.contains(&(x, y)),
+                })
+                .collect()
+        })
+        .collect();
+    let exported = ExportedBoard {
+        x_size: prompts.x_size,
+        y_size: prompts.y_size,
+        game_mode: board.config.mode.game_mode.to_string(),
+        winner,
+        rows,
+    };
+    serde_json::to_string_pretty(&exported).unwrap_or_default()
+}
+
+pub fn export(board: &Board, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Svg => to_svg(board),
+        ExportFormat::Html => to_html(board),
+        ExportFormat::Csv => to_csv(board),
+        ExportFormat::Json => to_json(board),
+    }
+}
+
+/// Path in the export directory named after the current time
+pub fn default_path(format: ExportFormat) -> PathBuf {
+    Path::new(EXPORT_DIR).join(format!("board-{}.{}", unix_now(), format.extension()))
+}
+
+/// Write an export of the board, creating missing directories
+pub fn save(board: &Board, format: ExportFormat, path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, export(board, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::teams::TeamStyle;
+
+    use super::*;
+
+    fn team(index: usize) -> Team {
+        Team::new(index).unwrap()
+    }
+
+    /// Default board with a custom style for the first team, which claimed the top left field
+    fn styled_board() -> Board {
+        let mut board = Board::default();
+        board.config.team_styles.set(
+            team(0),
+            TeamStyle {
+                name: "Cats, \"Dogs\" & Co".to_owned(),
+                color: [0x12, 0xab, 0xef],
+            },
+        );
+        assert!(board.claim(0, 0, team(0)));
+        board
+    }
+
+    #[test]
+    fn csv_escapes_values() {
+        let mut board = styled_board();
+        *board.config.prompts.prompt_mut(1, 0) = "a, \"b\"\nc".to_owned();
+        let csv = to_csv(&board);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("x,y,prompt,field,claimed_by,pending,winning")
+        );
+        assert_eq!(
+            lines.next(),
+            Some(r#"0,0,,Normal,"Cats, ""Dogs"" & Co",,false"#)
+        );
+        assert_eq!(lines.next(), Some(r#"1,0,"a, ""b"""#));
+        assert_eq!(lines.next(), Some(r#"c",Normal,,,false"#));
+    }
+
+    #[test]
+    fn svg_and_html_use_team_styles() {
+        let board = styled_board();
+        let svg = to_svg(&board);
+        assert!(svg.contains(r##"fill="#12abef""##));
+        let html = to_html(&board);
+        assert!(html.contains(&svg));
+        assert!(html.contains(
+            r#"<tr><td style="color: #12abef">Cats, &quot;Dogs&quot; &amp; Co</td><td>1 fields</td>"#
+        ));
+        assert!(!html.contains(&team(0).name()));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let board = styled_board();
+        let json: serde_json::Value = serde_json::from_str(&to_json(&board)).unwrap();
+        assert_eq!(json["x_size"], board.config.prompts.x_size);
+        assert_eq!(json["y_size"], board.config.prompts.y_size);
+        let rows = json["rows"].as_array().unwrap();
+        assert_eq!(rows.len(), board.config.prompts.y_size as usize);
+        let claimed_by: Vec<Team> =
+            serde_json::from_value(rows[0][0]["claimed_by"].clone()).unwrap();
+        assert_eq!(claimed_by, vec![team(0)]);
+        let kind: FieldKind = serde_json::from_value(rows[0][1]["kind"].clone()).unwrap();
+        assert_eq!(kind, board.field(1, 0));
+        assert_eq!(rows[0][1]["claimed_by"], serde_json::json!([]));
+    }
+}
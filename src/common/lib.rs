@@ -5,7 +5,11 @@ use bevy::prelude::*;
 use self::bingo::{Board, BoardMode, BoardPrompts};
 
 pub mod bingo;
+pub mod export;
+pub mod proof;
 pub mod protocol;
+pub mod replay;
+pub mod roles;
 pub mod teams;
 
 #[derive(Resource, Default)]
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Size of a single image chunk sent over the network
+pub const PROOF_CHUNK_SIZE: usize = 64 * 1024;
+/// Largest accepted proof image
+pub const MAX_PROOF_IMAGE_SIZE: u32 = 2 * 1024 * 1024;
+/// Longest accepted proof note
+pub const MAX_PROOF_NOTE_LENGTH: usize = 500;
+
+/// Description of a proof, sent ahead of its image chunks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProofHeader {
+    pub note: String,
+    pub image_name: Option<String>,
+    pub image_size: u32,
+}
+
+impl ProofHeader {
+    pub fn is_valid(&self) -> bool {
+        self.note.chars().count() <= MAX_PROOF_NOTE_LENGTH
+            && self.image_size <= MAX_PROOF_IMAGE_SIZE
+            && (self.image_name.is_some() || self.image_size == 0)
+    }
+}
+
+/// Proof attached to a claim, assembled from chunks
+#[derive(Debug, Clone)]
+pub struct Proof {
+    pub header: ProofHeader,
+    pub image: Vec<u8>,
+}
+
+impl Proof {
+    /// Start an empty proof, the image grows as chunks arrive instead of trusting the header
+    pub fn new(header: ProofHeader) -> Self {
+        Self {
+            image: Vec::new(),
+            header,
+        }
+    }
+
+    /// Append an image chunk, returns false if it exceeds the announced size
+    pub fn push_chunk(&mut self, data: &[u8]) -> bool {
+        if self.image.len() + data.len() > self.header.image_size as usize {
+            return false;
+        }
+        self.image.extend_from_slice(data);
+        true
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.image.len() == self.header.image_size as usize
+    }
+
+    /// Fraction of the image received so far
+    pub fn progress(&self) -> f32 {
+        match self.header.image_size {
+            0 => 1.0,
+            size => self.image.len() as f32 / size as f32,
+        }
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = &[u8]> {
+        self.image.chunks(PROOF_CHUNK_SIZE)
+    }
+}
@@ -4,17 +4,29 @@ use bevy_quinnet::shared::ClientId;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    bingo::{BoardActivity, BoardMode, BoardPrompts},
-    teams::Team,
+    bingo::{format_match_time, BoardActivity, BoardMode, BoardPrompts, FieldClaim},
+    proof::ProofHeader,
+    roles::{CoHostPermissions, Permission, Role},
+    teams::{Team, TeamSettings, TeamStyles},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ClientMessage {
     Join {
         name: String,
+        /// Required when the server has a password
+        password: Option<String>,
+        /// Required when the host set a room password
+        room_password: Option<String>,
     },
     Disconnect {},
     ChangeTeam(Option<Team>),
+    /// Join one of the active teams with the fewest players
+    JoinRandomTeam,
+    SetTeamSettings(TeamSettings),
+    SetTeamStyles(TeamStyles),
+    /// Even out the team sizes, moving players out of inactive teams
+    BalanceTeams,
     UpdateActivity {
         team: Team,
         x: u8,
@@ -25,20 +37,150 @@ pub enum ClientMessage {
     SetMode(BoardMode),
     ResetActivity,
     Kick(ClientId),
+    /// Kick a client and reject its username until the ban expires, permanently if `None`
+    Ban {
+        client_id: ClientId,
+        duration_secs: Option<u64>,
+        /// Also reject the client's address
+        ban_ip: bool,
+    },
+    /// Grant or revoke one of [`Role::ASSIGNABLE`]
+    SetRole {
+        client_id: ClientId,
+        role: Role,
+        granted: bool,
+    },
+    SetCoHostPermissions(CoHostPermissions),
+    /// Make another client the host, the sender loses its host role
+    TransferHost(ClientId),
+    ReviewClaim {
+        claim: FieldClaim,
+        approve: bool,
+    },
+    /// Start of a proof upload, followed by image chunks
+    AttachProof {
+        claim: FieldClaim,
+        header: ProofHeader,
+    },
+    ProofChunk {
+        claim: FieldClaim,
+        data: Vec<u8>,
+    },
+    RequestProof(FieldClaim),
+    /// Change the password required to join the room, `None` removes it
+    SetRoomPassword(Option<String>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientProps {
     pub is_host: bool,
+    #[serde(default)]
+    pub is_referee: bool,
+    #[serde(default)]
+    pub is_co_host: bool,
+    #[serde(default)]
+    pub is_caster: bool,
     pub username: String,
     pub team: Option<Team>,
 }
 
+impl ClientProps {
+    pub fn has_role(&self, role: Role) -> bool {
+        match role {
+            Role::Host => self.is_host,
+            Role::CoHost => self.is_co_host,
+            Role::Referee => self.is_referee,
+            Role::Caster => self.is_caster,
+            Role::Player => self.team.is_some(),
+            Role::Spectator => self.team.is_none(),
+        }
+    }
+
+    /// All roles of the client, most privileged first
+    pub fn roles(&self) -> impl Iterator<Item = Role> + '_ {
+        [
+            Role::Host,
+            Role::CoHost,
+            Role::Referee,
+            Role::Caster,
+            Role::Player,
+            Role::Spectator,
+        ]
+        .into_iter()
+        .filter(|role| self.has_role(*role))
+    }
+
+    /// Grant or revoke an assignable role, returns false for other roles
+    pub fn set_role(&mut self, role: Role, granted: bool) -> bool {
+        match role {
+            Role::CoHost => self.is_co_host = granted && !self.is_host,
+            Role::Referee => self.is_referee = granted,
+            Role::Caster => self.is_caster = granted,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Whether any role of the client grants the permission
+    pub fn can(&self, permission: Permission, co_host: &CoHostPermissions) -> bool {
+        self.roles()
+            .any(|role| permission.granted_to(role, co_host))
+    }
+}
+
+fn ban_duration(remaining_secs: &Option<u64>) -> String {
+    match remaining_secs {
+        Some(secs) => format!(" for another {}", format_match_time(*secs)),
+        None => String::new(),
+    }
+}
+
+/// Reason the server refused to let a client join
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, thiserror::Error)]
+pub enum JoinRejection {
+    #[error("wrong server password")]
+    WrongServerPassword,
+    #[error("wrong room password")]
+    WrongRoomPassword,
+    #[error("you are banned from this server{}", ban_duration(.remaining_secs))]
+    Banned {
+        /// Seconds until the ban expires, permanent if `None`
+        remaining_secs: Option<u64>,
+    },
+    #[error("username is already taken")]
+    UsernameTaken,
+    #[error("too many failed attempts to join, try again later")]
+    TooManyAttempts,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ServerMessage {
     InitClient(ClientId),
     SetClients(HashMap<ClientId, ClientProps>),
+    SetCoHostPermissions(CoHostPermissions),
+    SetTeamSettings(TeamSettings),
+    /// Team names and colors of the room
+    SetTeamStyles(TeamStyles),
     SetMode(BoardMode),
     SetPrompts(BoardPrompts),
     SetActivity(BoardActivity),
+    /// Start of a requested proof, followed by image chunks, `None` if there is no proof
+    ProofHeader {
+        claim: FieldClaim,
+        header: Option<ProofHeader>,
+    },
+    ProofChunk {
+        claim: FieldClaim,
+        data: Vec<u8>,
+    },
+    /// Message from the server admin
+    Announcement(String),
+    /// The server is about to close all connections
+    ServerShutdown {
+        reason: String,
+        /// Seconds after which the server is expected to be back
+        reconnect_after: Option<u32>,
+    },
+    /// The join was refused, the connection is closed shortly after
+    JoinRejected(JoinRejection),
 }
@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use bevy_quinnet::shared::ClientId;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    bingo::{
+        Board, BoardActivity, BoardConfig, BoardMode, BoardPrompts, ClaimAttribution, FieldClaim,
+    },
+    protocol::ClientProps,
+    teams::Team,
+};
+
+/// Version of the replay format written by this build
+pub const REPLAY_VERSION: u32 = 2;
+/// Extension of replay files
+pub const REPLAY_EXTENSION: &str = "replay";
+
+/// First line of a replay file, state of the match when recording started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub version: u32,
+    /// Server time of the start of the match, in seconds since the Unix epoch
+    pub started_at: u64,
+    pub config: BoardConfig,
+    /// Claims already on the board, recordings can start mid-match after a restore
+    pub activity: BoardActivity,
+    pub clients: HashMap<ClientId, ClientProps>,
+}
+
+/// Game event accepted by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    Join {
+        client_id: ClientId,
+        username: String,
+    },
+    Leave {
+        client_id: ClientId,
+    },
+    ChangeTeam {
+        client_id: ClientId,
+        team: Option<Team>,
+    },
+    Claim(ClaimAttribution),
+    Unclaim(FieldClaim),
+    ReviewClaim {
+        claim: FieldClaim,
+        approve: bool,
+    },
+    SetMode(BoardMode),
+    SetPrompts(BoardPrompts),
+    Win(Team),
+}
+
+/// Single line of a replay file after the header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEntry {
+    /// Milliseconds since the start of the recording
+    pub time_ms: u64,
+    pub event: ReplayEvent,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReplayError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("replay file is empty")]
+    Empty,
+    #[error("unsupported replay version {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// State of a match reconstructed from a replay
+#[derive(Debug, Clone)]
+pub struct ReplayState {
+    pub board: Board,
+    pub clients: HashMap<ClientId, ClientProps>,
+    pub winner: Option<Team>,
+    /// Server time of the start of the recording, in seconds since the Unix epoch
+    started_at: u64,
+}
+
+impl ReplayState {
+    fn new(header: &ReplayHeader) -> Self {
+        let board = Board {
+            config: header.config.clone(),
+            activity: header.activity.clone(),
+        };
+        Self {
+            winner: board.check_win(),
+            board,
+            clients: header.clients.clone(),
+            started_at: header.started_at,
+        }
+    }
+
+    /// Apply the next recorded event
+    pub fn apply(&mut self, entry: &ReplayEntry) {
+        match &entry.event {
+            ReplayEvent::Join {
+                client_id,
+                username,
+            } => {
+                let is_host = self.clients.is_empty();
+                self.clients.insert(
+                    *client_id,
+                    ClientProps {
+                        is_host,
+                        is_referee: false,
+                        is_co_host: false,
+                        is_caster: false,
+                        username: username.clone(),
+                        team: None,
+                    },
+                );
+            }
+            ReplayEvent::Leave { client_id } => {
+                self.clients.remove(client_id);
+            }
+            ReplayEvent::ChangeTeam { client_id, team } => {
+                if let Some(client) = self.clients.get_mut(client_id) {
+                    client.team = *team;
+                }
+            }
+            ReplayEvent::Claim(attribution) => {
+                // Timing rules were already enforced by the server when recording
+                let activity = &mut self.board.activity;
+                activity.claim_cooldowns.clear();
+                activity.claimed_at.fill(None);
+                let FieldClaim { team, x, y } = attribution.claim;
+                if self.board.claim(x, y, team) {
+                    self.board.activity.attribute(attribution.clone());
+                }
+            }
+            ReplayEvent::Unclaim(claim) => {
+                self.board.unclaim(claim.x, claim.y, &claim.team);
+            }
+            ReplayEvent::ReviewClaim { claim, approve } => {
+                self.board.review_claim(*claim, *approve);
+            }
+            ReplayEvent::SetMode(mode) => {
+                self.board.config.mode = mode.clone();
+                self.reset_activity(entry.time_ms);
+            }
+            ReplayEvent::SetPrompts(prompts) => {
+                self.board.config.prompts = prompts.clone();
+                self.reset_activity(entry.time_ms);
+            }
+            ReplayEvent::Win(team) => {
+                self.winner = Some(*team);
+            }
+        }
+    }
+
+    fn reset_activity(&mut self, time_ms: u64) {
+        self.board.reset_activity();
+        self.board.activity.started_at = self.started_at + time_ms / 1000;
+        self.winner = None;
+    }
+}
+
+/// Recorded match loaded from a replay file
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    /// Events ordered by time
+    pub entries: Vec<ReplayEntry>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let file = std::fs::File::open(path)?;
+        Self::parse(BufReader::new(file))
+    }
+
+    /// Parse a replay, ignoring a truncated last line left by an interrupted server
+    pub fn parse(reader: impl BufRead) -> Result<Self, ReplayError> {
+        let mut lines = reader.lines();
+        let header: ReplayHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)?,
+            None => return Err(ReplayError::Empty),
+        };
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(header.version));
+        }
+
+        let mut entries = Vec::new();
+        let mut lines = lines.peekable();
+        while let Some(line) = lines.next() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(_) if lines.peek().is_none() => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(Self { header, entries })
+    }
+
+    /// Length of the recording in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        self.entries.last().map_or(0, |entry| entry.time_ms)
+    }
+
+    /// State of the match before any event
+    pub fn initial_state(&self) -> ReplayState {
+        ReplayState::new(&self.header)
+    }
+
+    /// State of the match after all events up to the given time
+    pub fn state_at(&self, time_ms: u64) -> ReplayState {
+        let mut state = self.initial_state();
+        for entry in self.entries.iter().take_while(|e| e.time_ms <= time_ms) {
+            state.apply(entry);
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(version: u32, board: &Board) -> String {
+        serde_json::to_string(&ReplayHeader {
+            version,
+            started_at: board.activity.started_at,
+            config: board.config.clone(),
+            activity: board.activity.clone(),
+            clients: HashMap::new(),
+        })
+        .unwrap()
+    }
+
+    fn entry(time_ms: u64, event: ReplayEvent) -> String {
+        serde_json::to_string(&ReplayEntry { time_ms, event }).unwrap()
+    }
+
+    fn claim(x: u8, y: u8) -> ReplayEvent {
+        ReplayEvent::Claim(ClaimAttribution {
+            claim: FieldClaim {
+                team: Team::new(0).unwrap(),
+                x,
+                y,
+            },
+            client_id: 1,
+            username: "player".to_owned(),
+            timestamp: 0,
+        })
+    }
+
+    fn parse(lines: &[String]) -> Result<Replay, ReplayError> {
+        Replay::parse(lines.join("\n").as_bytes())
+    }
+
+    #[test]
+    fn parse_entries() {
+        let lines = [
+            header(REPLAY_VERSION, &Board::default()),
+            entry(100, claim(0, 0)),
+            String::new(),
+            entry(2500, claim(1, 0)),
+        ];
+        let replay = parse(&lines).unwrap();
+        assert_eq!(replay.entries.len(), 2);
+        assert_eq!(replay.duration_ms(), 2500);
+
+        let team = Team::new(0).unwrap();
+        let state = replay.state_at(1000);
+        assert!(state.board.is_active(0, 0, &team));
+        assert!(!state.board.is_active(1, 0, &team));
+        assert!(replay.state_at(2500).board.is_active(1, 0, &team));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let lines = [header(REPLAY_VERSION + 1, &Board::default())];
+        assert!(matches!(
+            parse(&lines),
+            Err(ReplayError::UnsupportedVersion(version)) if version == REPLAY_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_file() {
+        assert!(matches!(parse(&[]), Err(ReplayError::Empty)));
+    }
+
+    #[test]
+    fn ignores_truncated_last_line() {
+        let mut last = entry(200, claim(1, 0));
+        last.truncate(last.len() / 2);
+        let lines = [
+            header(REPLAY_VERSION, &Board::default()),
+            entry(100, claim(0, 0)),
+            last,
+        ];
+        assert_eq!(parse(&lines).unwrap().entries.len(), 1);
+    }
+
+    #[test]
+    fn rejects_broken_line_in_the_middle() {
+        let lines = [
+            header(REPLAY_VERSION, &Board::default()),
+            "{".to_owned(),
+            entry(100, claim(0, 0)),
+        ];
+        assert!(matches!(parse(&lines), Err(ReplayError::Json(_))));
+    }
+
+    #[test]
+    fn starts_from_recorded_activity() {
+        let team = Team::new(0).unwrap();
+        let mut board = Board::default();
+        assert!(board.claim(2, 2, team));
+        let replay = parse(&[header(REPLAY_VERSION, &board)]).unwrap();
+        assert!(replay.initial_state().board.is_active(2, 2, &team));
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Role of a client in the room, a client can have several at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    Host,
+    CoHost,
+    Referee,
+    /// Commentator who can look at claims and proofs without playing
+    Caster,
+    /// Client in a team
+    Player,
+    /// Client without a team
+    Spectator,
+}
+
+impl Role {
+    /// Roles the host can grant and revoke, the others follow from the host and the team
+    pub const ASSIGNABLE: [Role; 3] = [Role::CoHost, Role::Referee, Role::Caster];
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Role::Host => "host",
+            Role::CoHost => "co-host",
+            Role::Referee => "referee",
+            Role::Caster => "caster",
+            Role::Player => "player",
+            Role::Spectator => "spectator",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Action that requires a role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Change the prompts and the game mode
+    EditBoard,
+    /// Clear all claims
+    ResetBoard,
+    SetRoomPassword,
+    Kick,
+    Ban,
+    /// Grant and revoke assignable roles and set co-host permissions
+    ManageRoles,
+    TransferHost,
+    /// Change the team limits and balance the teams
+    ManageTeams,
+    /// Claim and unclaim fields for the own team
+    ClaimFields,
+    ReviewClaims,
+    /// View claims and their proofs
+    InspectProofs,
+}
+
+impl Permission {
+    /// The permission matrix, whether the role grants this permission
+    pub fn granted_to(self, role: Role, co_host: &CoHostPermissions) -> bool {
+        match (self, role) {
+            (Permission::ClaimFields, role) => role == Role::Player,
+            (_, Role::Host) => true,
+            (Permission::EditBoard | Permission::ManageTeams, Role::CoHost) => co_host.edit_board,
+            (Permission::ResetBoard, Role::CoHost) => co_host.reset,
+            (Permission::Kick, Role::CoHost) => co_host.kick,
+            (Permission::ReviewClaims, Role::Referee) => true,
+            (Permission::InspectProofs, Role::Referee | Role::Caster) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Host permissions the host lets co-hosts have
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CoHostPermissions {
+    /// Change the prompts, the game mode and the teams
+    pub edit_board: bool,
+    /// Clear all claims
+    pub reset: bool,
+    pub kick: bool,
+}
+
+impl Default for CoHostPermissions {
+    fn default() -> Self {
+        Self {
+            edit_board: true,
+            reset: true,
+            kick: false,
+        }
+    }
+}
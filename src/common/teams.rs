@@ -1,43 +1,386 @@
-use egui::Color32;
-use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Display};
 
-#[derive(PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Clone, Copy)]
-pub enum Team {
-    Red,
-    Green,
-    Blue,
-    Cyan,
-    Pink,
-    Magenta,
-    Purple,
-    Yellow,
+use egui::{ecolor, Color32};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Most teams a room can have
+pub const MAX_TEAMS: usize = 32;
+
+/// Names of the first teams, the others are numbered
+const NAMES: [&str; 8] = [
+    "Red", "Green", "Blue", "Cyan", "Pink", "Magenta", "Purple", "Yellow",
+];
+
+/// Team ID, stable across renames and color changes
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, PartialOrd, Ord)]
+pub struct Team(u8);
+
+static ALL: [Team; MAX_TEAMS] = {
+    let mut all = [Team(0); MAX_TEAMS];
+    let mut i = 0;
+    while i < MAX_TEAMS {
+        all[i] = Team(i as u8);
+        i += 1;
+    }
+    all
+};
+
+fn hex_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
 }
 
 impl Team {
+    pub fn new(index: usize) -> Option<Self> {
+        ALL.get(index).copied()
+    }
+
+    /// Position of the team in [`Team::iter`]
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    /// Team by its default name or its 1-based number, as typed by users
+    pub fn parse(text: &str) -> Option<Self> {
+        if let Some(index) = NAMES
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(text))
+        {
+            return Self::new(index);
+        }
+        let number = text.trim_start_matches("Team ").parse::<usize>().ok()?;
+        Self::new(number.checked_sub(1)?)
+    }
+
     pub fn color(&self) -> Color32 {
-        match self {
-            Team::Red => Color32::RED,
-            Team::Green => Color32::GREEN,
-            Team::Blue => Color32::BLUE,
-            Team::Cyan => Color32::from_rgb(0, 183, 235),
-            Team::Pink => Color32::from_rgb(255, 105, 180),
-            Team::Magenta => Color32::from_rgb(255, 0, 144),
-            Team::Purple => Color32::from_rgb(186, 85, 211),
-            Team::Yellow => Color32::YELLOW,
+        match self.0 {
+            0 => Color32::RED,
+            1 => Color32::GREEN,
+            2 => Color32::BLUE,
+            3 => Color32::from_rgb(0, 183, 235),
+            4 => Color32::from_rgb(255, 105, 180),
+            5 => Color32::from_rgb(255, 0, 144),
+            6 => Color32::from_rgb(186, 85, 211),
+            7 => Color32::YELLOW,
+            index => generated_color(index),
+        }
+    }
+
+    /// Color as a `#rrggbb` string for web exports
+    pub fn hex_color(&self) -> String {
+        hex_color(self.color())
+    }
+
+    /// Default name, also accepted by [`Team::parse`]
+    pub fn name(&self) -> String {
+        match NAMES.get(self.index()) {
+            Some(name) => name.to_string(),
+            None => format!("Team {}", self.0 + 1),
         }
     }
 
     pub fn iter() -> std::slice::Iter<'static, Self> {
-        [
-            Self::Red,
-            Self::Green,
-            Self::Blue,
-            Self::Cyan,
-            Self::Pink,
-            Self::Magenta,
-            Self::Purple,
-            Self::Yellow,
-        ]
-        .iter()
+        ALL.iter()
+    }
+}
+
+/// Color of a team past the named ones, hues are spread by the golden angle
+/// and brightness alternates so neighbouring teams stay apart
+fn generated_color(index: u8) -> Color32 {
+    const GOLDEN_RATIO: f32 = 0.618_034;
+    let hue = (index as f32 * GOLDEN_RATIO).fract();
+    let (saturation, value) = match index % 3 {
+        0 => (0.9, 1.0),
+        1 => (0.6, 0.85),
+        _ => (1.0, 0.65),
+    };
+    ecolor::Hsva::new(hue, saturation, value, 1.0).into()
+}
+
+impl Display for Team {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Named teams are written by name in human readable formats, so older saves stay readable
+impl Serialize for Team {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match NAMES.get(self.index()) {
+            Some(name) if serializer.is_human_readable() => serializer.serialize_str(name),
+            _ => serializer.serialize_u8(self.0),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Team {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(TeamVisitor)
+        } else {
+            deserializer.deserialize_u8(TeamVisitor)
+        }
+    }
+}
+
+struct TeamVisitor;
+
+impl Visitor<'_> for TeamVisitor {
+    type Value = Team;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a team name or an index below {}", MAX_TEAMS)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Team, E> {
+        usize::try_from(value)
+            .ok()
+            .and_then(Team::new)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+    }
+
+    /// A serialized name, or the index of a numbered team used as a map key.
+    /// Unlike [`Team::parse`], numbers are indexes here and not 1-based.
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Team, E> {
+        let index = match NAMES.iter().position(|name| *name == value) {
+            Some(index) => Some(index),
+            None => value.parse::<usize>().ok(),
+        };
+        index
+            .and_then(Team::new)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// Most marker columns in a row before markers wrap to another row
+const MAX_MARKER_COLUMNS: usize = 8;
+
+/// Placement of team markers, half of them in the top quarter of a field and half in the bottom one
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MarkerLayout {
+    pub columns: usize,
+    /// Rows in each quarter
+    pub rows: usize,
+}
+
+impl MarkerLayout {
+    pub fn new(team_count: usize) -> Self {
+        let per_quarter = team_count.div_ceil(2).max(1);
+        let rows = per_quarter.div_ceil(MAX_MARKER_COLUMNS);
+        Self {
+            columns: per_quarter.div_ceil(rows),
+            rows,
+        }
+    }
+
+    /// Marker position and size as fractions of the field size
+    pub fn marker(&self, index: usize) -> ((f32, f32), (f32, f32)) {
+        let per_quarter = self.columns * self.rows;
+        let (quarter, slot) = (index / per_quarter, index % per_quarter);
+        let row = quarter * 3 * self.rows + slot / self.columns;
+        let size = (1.0 / self.columns as f32, 0.25 / self.rows as f32);
+        let pos = ((slot % self.columns) as f32 * size.0, row as f32 * size.1);
+        (pos, size)
+    }
+}
+
+/// Limits on the teams players can join, set by the host
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TeamSettings {
+    /// Number of joinable teams, taken from the start of [`Team::iter`]
+    pub count: u8,
+    /// Players allowed per team, unlimited if `None`
+    pub max_size: Option<u8>,
+}
+
+/// Teams a room starts with
+const DEFAULT_TEAMS: u8 = 8;
+
+impl Default for TeamSettings {
+    fn default() -> Self {
+        Self {
+            count: DEFAULT_TEAMS,
+            max_size: None,
+        }
+    }
+}
+
+impl TeamSettings {
+    /// Clamp the settings to at least one team of at least one player
+    pub fn sanitized(self) -> Self {
+        Self {
+            count: self.count.clamp(1, MAX_TEAMS as u8),
+            max_size: self.max_size.map(|size| size.max(1)),
+        }
+    }
+
+    /// Teams players can join
+    pub fn active(&self) -> impl Iterator<Item = Team> {
+        Team::iter().take(self.count as usize).copied()
+    }
+
+    pub fn is_active(&self, team: Team) -> bool {
+        self.active().any(|t| t == team)
+    }
+
+    /// Whether a team with the given number of players can take another one
+    pub fn has_room(&self, size: usize) -> bool {
+        match self.max_size {
+            Some(max) => size < max as usize,
+            None => true,
+        }
+    }
+}
+
+/// Longest team name the server accepts, in characters
+pub const MAX_TEAM_NAME_LEN: usize = 24;
+
+/// Name and color a team is shown with
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TeamStyle {
+    pub name: String,
+    pub color: [u8; 3],
+}
+
+impl TeamStyle {
+    pub fn default_for(team: Team) -> Self {
+        let color = team.color();
+        Self {
+            name: team.name(),
+            color: [color.r(), color.g(), color.b()],
+        }
+    }
+}
+
+/// Team names and colors picked by the host, teams without one use their defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TeamStyles {
+    custom: HashMap<Team, TeamStyle>,
+}
+
+impl TeamStyles {
+    pub fn get(&self, team: Team) -> TeamStyle {
+        self.custom
+            .get(&team)
+            .cloned()
+            .unwrap_or_else(|| TeamStyle::default_for(team))
+    }
+
+    pub fn name(&self, team: Team) -> String {
+        self.custom
+            .get(&team)
+            .map_or_else(|| team.name(), |style| style.name.clone())
+    }
+
+    pub fn color(&self, team: Team) -> Color32 {
+        self.custom.get(&team).map_or_else(
+            || team.color(),
+            |style| Color32::from_rgb(style.color[0], style.color[1], style.color[2]),
+        )
+    }
+
+    /// Color as a `#rrggbb` string for web exports
+    pub fn hex_color(&self, team: Team) -> String {
+        hex_color(self.color(team))
+    }
+
+    /// Change the style of a team, a style equal to the default is not stored
+    pub fn set(&mut self, team: Team, style: TeamStyle) {
+        if style == TeamStyle::default_for(team) {
+            self.custom.remove(&team);
+        } else {
+            self.custom.insert(team, style);
+        }
+    }
+
+    /// Trim names and cut them to [`MAX_TEAM_NAME_LEN`], empty names fall back to the default
+    pub fn sanitized(self) -> Self {
+        let mut styles = Self::default();
+        for (team, mut style) in self.custom {
+            style.name = style.name.trim().chars().take(MAX_TEAM_NAME_LEN).collect();
+            if style.name.is_empty() {
+                style.name = team.name();
+            }
+            styles.set(team, style);
+        }
+        styles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_layout_of_default_teams() {
+        let layout = MarkerLayout::new(8);
+        assert_eq!((layout.columns, layout.rows), (4, 1));
+        assert_eq!(layout.marker(0), ((0.0, 0.0), (0.25, 0.25)));
+        assert_eq!(layout.marker(3), ((0.75, 0.0), (0.25, 0.25)));
+        // The second half starts in the bottom quarter
+        assert_eq!(layout.marker(4), ((0.0, 0.75), (0.25, 0.25)));
+        assert_eq!(layout.marker(7), ((0.75, 0.75), (0.25, 0.25)));
+    }
+
+    #[test]
+    fn marker_layout_wraps_rows() {
+        let layout = MarkerLayout::new(MAX_TEAMS);
+        assert_eq!((layout.columns, layout.rows), (8, 2));
+        assert_eq!(layout.marker(8), ((0.0, 0.125), (0.125, 0.125)));
+        assert_eq!(layout.marker(16), ((0.0, 0.75), (0.125, 0.125)));
+        assert_eq!(layout.marker(31), ((0.875, 0.875), (0.125, 0.125)));
+    }
+
+    #[test]
+    fn markers_stay_inside_the_field() {
+        for count in 1..=MAX_TEAMS {
+            let layout = MarkerLayout::new(count);
+            for index in 0..count {
+                let ((x, y), (w, h)) = layout.marker(index);
+                assert!(
+                    x >= 0.0 && x + w <= 1.0 + f32::EPSILON,
+                    "{} {}",
+                    count,
+                    index
+                );
+                assert!(
+                    y >= 0.0 && y + h <= 1.0 + f32::EPSILON,
+                    "{} {}",
+                    count,
+                    index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn serde_keeps_names_of_default_teams() {
+        let red = Team::new(0).unwrap();
+        let tenth = Team::new(9).unwrap();
+        assert_eq!(serde_json::to_string(&red).unwrap(), r#""Red""#);
+        assert_eq!(serde_json::to_string(&tenth).unwrap(), "9");
+        assert_eq!(serde_json::from_str::<Team>("9").unwrap(), tenth);
+
+        let keys = HashMap::from([(red, 1), (tenth, 2)]);
+        let json = serde_json::to_string(&keys).unwrap();
+        assert_eq!(
+            serde_json::from_str::<HashMap<Team, u8>>(&json).unwrap(),
+            keys
+        );
+    }
+
+    #[test]
+    fn numbers_are_indexes_in_serde_and_1_based_in_parse() {
+        assert_eq!(
+            serde_json::from_str::<Team>(r#""3""#).unwrap(),
+            Team::new(3).unwrap()
+        );
+        assert_eq!(Team::parse("3"), Team::new(2));
+        assert_eq!(Team::parse("Team 10"), Team::new(9));
+        assert_eq!(Team::parse("cyan"), Team::new(3));
+        assert!(serde_json::from_str::<Team>(r#""cyan""#).is_err());
+        assert!(serde_json::from_str::<Team>(&MAX_TEAMS.to_string()).is_err());
     }
 }
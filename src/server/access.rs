@@ -0,0 +1,170 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use bevy_quinnet::{
+    server::{Endpoint, Server},
+    shared::ClientId,
+};
+
+use common::protocol::{JoinRejection, ServerMessage};
+use serde::{Deserialize, Serialize};
+
+use crate::{bans::Bans, config::ServerConfig, Clients};
+
+/// Time a rejected client has to receive the reason before it is disconnected
+const REJECTION_GRACE: Duration = Duration::from_secs(1);
+
+/// Room password and connections waiting to be closed after a rejected join
+#[derive(Resource, Default)]
+pub struct Access {
+    room_password: Option<PasswordHash>,
+    rejected: Vec<(ClientId, Instant)>,
+}
+
+/// Salted BLAKE3 hash of a password, so it is never kept or saved in plain text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordHash {
+    /// Random key the password is hashed with, hex encoded
+    salt: String,
+    /// Hex encoded
+    hash: String,
+}
+
+impl PasswordHash {
+    pub fn new(password: &str) -> Self {
+        let salt: [u8; blake3::KEY_LEN] = rand::random();
+        Self {
+            salt: blake3::Hash::from(salt).to_hex().to_string(),
+            hash: blake3::keyed_hash(&salt, password.as_bytes())
+                .to_hex()
+                .to_string(),
+        }
+    }
+
+    /// Whether the password hashes to the same value, a corrupted hash matches nothing
+    pub fn matches(&self, password: &str) -> bool {
+        let (Ok(salt), Ok(hash)) = (
+            blake3::Hash::from_hex(&self.salt),
+            blake3::Hash::from_hex(&self.hash),
+        ) else {
+            return false;
+        };
+        // Comparing hashes takes constant time
+        blake3::keyed_hash(salt.as_bytes(), password.as_bytes()) == hash
+    }
+}
+
+/// Credentials and name of a joining client
+pub struct JoinRequest<'a> {
+    pub name: &'a str,
+    /// Address of the client, `None` if it couldn't be found out
+    pub ip: Option<IpAddr>,
+    pub password: Option<&'a str>,
+    pub room_password: Option<&'a str>,
+}
+
+/// Whether the given password matches the required one, empty means no password
+fn matches(required: Option<&str>, given: Option<&str>) -> bool {
+    match required.filter(|p| !p.is_empty()) {
+        Some(required) => given == Some(required),
+        None => true,
+    }
+}
+
+impl Access {
+    /// Change the room password, an empty one removes it
+    pub fn set_room_password(&mut self, password: Option<String>) {
+        self.room_password = password
+            .filter(|p| !p.is_empty())
+            .map(|p| PasswordHash::new(&p));
+    }
+
+    /// Hash of the room password, to restore it with [`Access::set_room_password_hash`]
+    pub fn room_password_hash(&self) -> Option<&PasswordHash> {
+        self.room_password.as_ref()
+    }
+
+    pub fn set_room_password_hash(&mut self, hash: Option<PasswordHash>) {
+        self.room_password = hash;
+    }
+
+    /// Whether the client is waiting to be disconnected, its messages are ignored until then
+    pub fn is_rejected(&self, client_id: ClientId) -> bool {
+        self.rejected.iter().any(|(id, _)| *id == client_id)
+    }
+
+    /// Check a join, the server password is checked first so others don't leak to outsiders
+    pub fn check_join(
+        &self,
+        config: &ServerConfig,
+        bans: &Bans,
+        clients: &Clients,
+        request: &JoinRequest,
+    ) -> Result<(), JoinRejection> {
+        if !matches(Some(&config.password), request.password) {
+            return Err(JoinRejection::WrongServerPassword);
+        }
+        if let Some(ban) = bans.get(request.name, request.ip) {
+            return Err(JoinRejection::Banned {
+                remaining_secs: ban.remaining_secs(),
+            });
+        }
+        if let Some(room_password) = &self.room_password {
+            if !request
+                .room_password
+                .is_some_and(|p| room_password.matches(p))
+            {
+                return Err(JoinRejection::WrongRoomPassword);
+            }
+        }
+        if clients.data.values().any(|c| c.username == request.name) {
+            return Err(JoinRejection::UsernameTaken);
+        }
+        Ok(())
+    }
+
+    /// Tell the client why it can't join and disconnect it shortly after
+    pub fn reject(&mut self, endpoint: &mut Endpoint, client_id: ClientId, reason: JoinRejection) {
+        endpoint.try_send_message(client_id, ServerMessage::JoinRejected(reason));
+        self.rejected.push((client_id, Instant::now()));
+    }
+}
+
+pub fn disconnect_rejected(mut access: ResMut<Access>, mut server: ResMut<Server>) {
+    let endpoint = server.endpoint_mut();
+    access.rejected.retain(|(client_id, rejected_at)| {
+        if rejected_at.elapsed() < REJECTION_GRACE {
+            return true;
+        }
+        if endpoint.clients().contains(client_id) {
+            endpoint.try_disconnect_client(*client_id);
+        }
+        false
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_hash_matches_only_the_password() {
+        let hash = PasswordHash::new("secret");
+        assert!(hash.matches("secret"));
+        assert!(!hash.matches("Secret"));
+        assert!(!hash.matches(""));
+        assert!(!serde_json::to_string(&hash).unwrap().contains("secret"));
+    }
+
+    #[test]
+    fn password_hashes_are_salted() {
+        let (first, second) = (PasswordHash::new("secret"), PasswordHash::new("secret"));
+        assert_ne!(first.hash, second.hash);
+        let restored: PasswordHash =
+            serde_json::from_str(&serde_json::to_string(&first).unwrap()).unwrap();
+        assert!(restored.matches("secret"));
+    }
+}
@@ -0,0 +1,319 @@
+use std::{collections::HashMap, net::IpAddr, path::PathBuf};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_quinnet::{server::Server, shared::ClientId};
+use serde::Serialize;
+
+use common::{
+    bingo::{format_match_time, BoardActivity, BoardConfig, BoardMode, GameMode, WinCondition},
+    export::{self, ExportFormat},
+    protocol::{ClientProps, ServerMessage},
+    roles::Role,
+    teams::Team,
+    BoardRes,
+};
+
+use crate::{
+    bans::{self, Bans},
+    broadcast, kick,
+    peers::Peers,
+    proofs::Proofs,
+    replay::ReplayRecorder,
+    reset_board, set_mode, set_prompts, set_team_settings, set_team_styles, transfer_host, Clients,
+};
+
+const HELP: &str = "\
+Commands:
+  list                    List clients and their teams
+  kick <client>           Disconnect a client
+  ban <client> [time] [ip]
+                          Disconnect a client and reject its username, for a time like 30m, 12h or 7d,
+                          with ip also reject its address
+  ban <address> [time]    Disconnect and reject all clients from an IP address
+  unban <username|address>
+                          Allow a banned username or address again
+  bans                    List banned usernames and addresses
+  host <client>           Make a client the host
+  reset                   Clear all claims
+  load <path>             Load a board config from a JSON file
+  mode <mode>             Set the game mode (ffa, lockout, steal, invasion)
+  announce <text>         Show a message to all clients
+  export <format> [path]  Save the board as svg, html, csv or json
+Clients can be given by id or username.";
+
+#[derive(thiserror::Error, Debug)]
+pub enum AdminError {
+    #[error("unknown command {0}, type help for a list of commands")]
+    UnknownCommand(String),
+    #[error("missing argument, type help for usage")]
+    MissingArgument,
+    #[error("no client {0}")]
+    UnknownClient(String),
+    #[error("unknown game mode {0}")]
+    UnknownMode(String),
+    #[error("{0} does not allow domination, change the win condition first")]
+    InvalidWinCondition(GameMode),
+    #[error("address of {0} is unknown")]
+    UnknownAddress(String),
+    #[error("invalid duration {0}, use a number followed by s, m, h or d")]
+    InvalidDuration(String),
+    #[error("unknown export format {0}")]
+    UnknownFormat(String),
+    #[error("board size does not match the number of prompts")]
+    InvalidBoard,
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Domination needs a game mode in which every field has a single owner
+fn check_win_condition(mode: &BoardMode) -> Result<(), AdminError> {
+    let domination = std::iter::once(&mode.win_condition)
+        .chain(mode.team_win_conditions.values())
+        .any(|condition| *condition == WinCondition::Domination);
+    if domination && !mode.game_mode.is_exclusive() {
+        return Err(AdminError::InvalidWinCondition(mode.game_mode));
+    }
+    Ok(())
+}
+
+fn parse_mode(name: &str) -> Result<GameMode, AdminError> {
+    match name.to_lowercase().as_str() {
+        "ffa" => Ok(GameMode::FFA),
+        "lockout" => Ok(GameMode::Lockout),
+        "steal" => Ok(GameMode::Steal),
+        "invasion" => Ok(GameMode::Invasion),
+        _ => Err(AdminError::UnknownMode(name.to_owned())),
+    }
+}
+
+/// Find a client by id or username
+fn find_client(clients: &Clients, name: &str) -> Result<ClientId, AdminError> {
+    let by_id = name
+        .parse::<ClientId>()
+        .ok()
+        .filter(|id| clients.data.contains_key(id));
+    by_id
+        .or_else(|| {
+            clients
+                .data
+                .iter()
+                .find(|(_, client)| client.username == name)
+                .map(|(id, _)| *id)
+        })
+        .ok_or_else(|| AdminError::UnknownClient(name.to_owned()))
+}
+
+/// Snapshot of the game served by the HTTP API
+#[derive(Serialize)]
+pub struct Status<'a> {
+    pub clients: &'a HashMap<ClientId, ClientProps>,
+    pub config: &'a BoardConfig,
+    pub activity: &'a BoardActivity,
+    pub winner: Option<Team>,
+}
+
+/// Server state changed by admin commands from the console or the HTTP API
+#[derive(SystemParam)]
+pub struct Admin<'w> {
+    server: ResMut<'w, Server>,
+    clients: ResMut<'w, Clients>,
+    board: ResMut<'w, BoardRes>,
+    proofs: ResMut<'w, Proofs>,
+    recorder: ResMut<'w, ReplayRecorder>,
+    bans: ResMut<'w, Bans>,
+    peers: Res<'w, Peers>,
+}
+
+impl<'w> Admin<'w> {
+    pub fn status(&self) -> Status<'_> {
+        Status {
+            clients: &self.clients.data,
+            config: &self.board.config,
+            activity: &self.board.activity,
+            winner: self.board.check_win(),
+        }
+    }
+
+    /// Run a command line, returns its output
+    pub fn execute(&mut self, line: &str) -> Result<String, AdminError> {
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+        let require_argument = || match argument.is_empty() {
+            true => Err(AdminError::MissingArgument),
+            false => Ok(argument),
+        };
+        let Self {
+            server,
+            clients,
+            board,
+            proofs,
+            recorder,
+            bans,
+            peers,
+        } = self;
+        let endpoint = server.endpoint_mut();
+
+        match command {
+            "help" => Ok(HELP.to_owned()),
+            "list" => {
+                let mut list = clients.data.iter().collect::<Vec<_>>();
+                list.sort_by_key(|(id, _)| **id);
+                let lines = list
+                    .into_iter()
+                    .map(|(id, client)| {
+                        let roles = client
+                            .roles()
+                            .filter(|role| *role != Role::Player && *role != Role::Spectator)
+                            .map(|role| role.to_string())
+                            .collect::<Vec<_>>();
+                        let team = client
+                            .team
+                            .map_or("Spectator".to_owned(), |team| team.to_string());
+                        format!(
+                            "{}\t{}\t{}\t{}",
+                            id,
+                            client.username,
+                            team,
+                            roles.join(", ")
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                Ok(lines.join("\n"))
+            }
+            "kick" => {
+                let id = find_client(clients, require_argument()?)?;
+                kick(endpoint, clients, recorder, id);
+                Ok(format!("Kicked {}", id))
+            }
+            "ban" => {
+                let mut words = require_argument()?.split_whitespace();
+                let name = words.next().ok_or(AdminError::MissingArgument)?;
+                let (mut duration, mut ban_ip) = (None, false);
+                for word in words {
+                    match word {
+                        "ip" => ban_ip = true,
+                        _ => duration = Some(word),
+                    }
+                }
+                let duration_secs = duration
+                    .map(|d| {
+                        bans::parse_duration(d).ok_or(AdminError::InvalidDuration(d.to_owned()))
+                    })
+                    .transpose()?;
+                let (username, ip) = match name.parse::<IpAddr>() {
+                    Ok(ip) => (None, Some(ip)),
+                    Err(_) => {
+                        let target = find_client(clients, name).ok();
+                        let ip = match (ban_ip, target) {
+                            (false, _) => None,
+                            (true, Some(id)) => Some(
+                                peers
+                                    .ip(id)
+                                    .ok_or(AdminError::UnknownAddress(name.to_owned()))?,
+                            ),
+                            (true, None) => return Err(AdminError::UnknownClient(name.to_owned())),
+                        };
+                        let username =
+                            target.map_or(name.to_owned(), |id| clients.data[&id].username.clone());
+                        (Some(username), ip)
+                    }
+                };
+                let banned = clients
+                    .data
+                    .iter()
+                    .filter(|(id, client)| {
+                        username.as_ref() == Some(&client.username)
+                            || ip.is_some() && peers.ip(**id) == ip
+                    })
+                    .map(|(id, _)| *id)
+                    .collect::<Vec<_>>();
+                for id in banned {
+                    kick(endpoint, clients, recorder, id);
+                }
+                let names = username
+                    .iter()
+                    .cloned()
+                    .chain(ip.map(|ip| ip.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                let output = match duration {
+                    Some(duration) => format!("Banned {} for {}", names, duration),
+                    None => format!("Banned {}", names),
+                };
+                bans.ban(username, ip, duration_secs);
+                Ok(output)
+            }
+            "unban" => {
+                let name = require_argument()?;
+                match bans.unban(name) {
+                    true => Ok(format!("Unbanned {}", name)),
+                    false => Ok(format!("{} is not banned", name)),
+                }
+            }
+            "bans" => {
+                let lines = bans
+                    .list()
+                    .into_iter()
+                    .map(|(username, ban)| match ban.remaining_secs() {
+                        Some(secs) => format!("{}\t{}", username, format_match_time(secs)),
+                        None => format!("{}\tpermanent", username),
+                    })
+                    .collect::<Vec<_>>();
+                Ok(lines.join("\n"))
+            }
+            "host" => {
+                let id = find_client(clients, require_argument()?)?;
+                transfer_host(endpoint, clients, id);
+                Ok(format!("Made {} the host", id))
+            }
+            "reset" => {
+                reset_board(board, endpoint, clients, proofs, recorder);
+                Ok("Board reset".to_owned())
+            }
+            "load" => {
+                let data = std::fs::read_to_string(require_argument()?)?;
+                let config = serde_json::from_str::<BoardConfig>(&data)?;
+                if !config.prompts.is_valid() {
+                    return Err(AdminError::InvalidBoard);
+                }
+                check_win_condition(&config.mode)?;
+                set_prompts(board, endpoint, clients, proofs, recorder, config.prompts);
+                set_mode(board, endpoint, clients, proofs, recorder, config.mode);
+                set_team_settings(board, endpoint, clients, recorder, config.teams);
+                set_team_styles(board, endpoint, clients, config.team_styles);
+                Ok("Board loaded".to_owned())
+            }
+            "mode" => {
+                let game_mode = parse_mode(require_argument()?)?;
+                let mut mode = board.config.mode.clone();
+                mode.game_mode = game_mode;
+                check_win_condition(&mode)?;
+                set_mode(board, endpoint, clients, proofs, recorder, mode);
+                Ok(format!("Game mode set to {}", game_mode))
+            }
+            "announce" => {
+                let text = require_argument()?;
+                broadcast(
+                    endpoint,
+                    clients,
+                    ServerMessage::Announcement(text.to_owned()),
+                );
+                Ok("Announcement sent".to_owned())
+            }
+            "export" => {
+                let (name, path) = require_argument()?
+                    .split_once(' ')
+                    .map_or((argument, None), |(name, path)| (name, Some(path.trim())));
+                let format = ExportFormat::parse(name)
+                    .ok_or_else(|| AdminError::UnknownFormat(name.to_owned()))?;
+                let path = path.map_or_else(|| export::default_path(format), PathBuf::from);
+                export::save(board, format, &path)?;
+                Ok(format!("Exported board to {}", path.display()))
+            }
+            _ => Err(AdminError::UnknownCommand(command.to_owned())),
+        }
+    }
+}
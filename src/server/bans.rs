@@ -0,0 +1,223 @@
+use std::{collections::HashMap, net::IpAddr, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use common::bingo::unix_now;
+
+/// File the ban list is kept in
+const BANS_PATH: &str = "bans.json";
+
+#[derive(thiserror::Error, Debug)]
+pub enum BansError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Ban of a single username
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Ban {
+    /// Seconds since the Unix epoch after which the ban is lifted, permanent if `None`
+    pub expires_at: Option<u64>,
+}
+
+impl Ban {
+    fn is_active(&self, now: u64) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now < expires_at,
+            None => true,
+        }
+    }
+
+    /// Seconds until the ban is lifted, `None` if it's permanent
+    pub fn remaining_secs(&self) -> Option<u64> {
+        self.expires_at
+            .map(|expires_at| expires_at.saturating_sub(unix_now()))
+    }
+}
+
+/// Usernames and addresses rejected when joining, saved to disk on every change
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct Bans {
+    usernames: HashMap<String, Ban>,
+    #[serde(default)]
+    ips: HashMap<IpAddr, Ban>,
+}
+
+/// Parse a duration like `90s`, `30m`, `12h` or `7d` into seconds
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let unit = text.chars().last()?;
+    let value = &text[..text.len() - unit.len_utf8()];
+    let multiplier = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    value.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+impl Bans {
+    /// Active ban of the username or the address, the username's is checked first
+    pub fn get(&self, username: &str, ip: Option<IpAddr>) -> Option<Ban> {
+        self.get_at(username, ip, unix_now())
+    }
+
+    fn get_at(&self, username: &str, ip: Option<IpAddr>, now: u64) -> Option<Ban> {
+        let ip_ban = ip.and_then(|ip| self.ips.get(&ip));
+        self.usernames
+            .get(username)
+            .filter(|ban| ban.is_active(now))
+            .or(ip_ban.filter(|ban| ban.is_active(now)))
+            .copied()
+    }
+
+    /// Active bans of usernames and addresses, sorted
+    pub fn list(&self) -> Vec<(String, &Ban)> {
+        let now = unix_now();
+        let mut list = self
+            .usernames
+            .iter()
+            .map(|(username, ban)| (username.clone(), ban))
+            .chain(self.ips.iter().map(|(ip, ban)| (ip.to_string(), ban)))
+            .filter(|(_, ban)| ban.is_active(now))
+            .collect::<Vec<_>>();
+        list.sort_by(|(a, _), (b, _)| a.cmp(b));
+        list
+    }
+
+    /// Ban a username, and its address if given, for the given number of seconds or permanently
+    pub fn ban(
+        &mut self,
+        username: Option<String>,
+        ip: Option<IpAddr>,
+        duration_secs: Option<u64>,
+    ) {
+        let expires_at = duration_secs.map(|secs| unix_now().saturating_add(secs));
+        if let Some(username) = username {
+            self.usernames.insert(username, Ban { expires_at });
+        }
+        if let Some(ip) = ip {
+            self.ips.insert(ip, Ban { expires_at });
+        }
+        self.save();
+    }
+
+    /// Lift the ban of a username or an address, returns false if it wasn't banned
+    pub fn unban(&mut self, target: &str) -> bool {
+        let removed = match target.parse::<IpAddr>() {
+            Ok(ip) => self.ips.remove(&ip).is_some(),
+            Err(_) => self.usernames.remove(target).is_some(),
+        };
+        self.save();
+        removed
+    }
+
+    fn save(&mut self) {
+        let now = unix_now();
+        self.usernames.retain(|_, ban| ban.is_active(now));
+        self.ips.retain(|_, ban| ban.is_active(now));
+        if let Err(e) = self.write() {
+            warn!("Failed to save {} due to error {}", BANS_PATH, e);
+        }
+    }
+
+    fn write(&self) -> Result<(), BansError> {
+        let tmp_path = format!("{}.tmp", BANS_PATH);
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(tmp_path, BANS_PATH)?;
+        Ok(())
+    }
+}
+
+fn load() -> Result<Bans, BansError> {
+    let data = std::fs::read_to_string(BANS_PATH)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn load_bans(mut bans: ResMut<Bans>) {
+    if !Path::new(BANS_PATH).exists() {
+        return;
+    }
+    match load() {
+        Ok(loaded) => {
+            *bans = loaded;
+            info!("Loaded {} bans from {}", bans.list().len(), BANS_PATH);
+        }
+        Err(e) => warn!("Failed to load {} due to error {}", BANS_PATH, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("90s"), Some(90));
+        assert_eq!(parse_duration("30m"), Some(30 * 60));
+        assert_eq!(parse_duration("12h"), Some(12 * 60 * 60));
+        assert_eq!(parse_duration("7d"), Some(7 * 24 * 60 * 60));
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("10"), None);
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration("5w"), None);
+        assert_eq!(parse_duration("-5m"), None);
+        assert_eq!(parse_duration("1.5h"), None);
+        assert_eq!(parse_duration("10é"), None);
+        assert_eq!(parse_duration(&format!("{}d", u64::MAX)), None);
+    }
+
+    #[test]
+    fn ip_ban_expiry() {
+        let ip = "192.0.2.7".parse().unwrap();
+        let other_ip = "192.0.2.8".parse().unwrap();
+        let mut bans = Bans::default();
+        bans.ips.insert(
+            ip,
+            Ban {
+                expires_at: Some(100),
+            },
+        );
+        assert!(bans.get_at("anyone", Some(ip), 99).is_some());
+        assert!(bans.get_at("anyone", Some(ip), 100).is_none());
+        assert!(bans.get_at("anyone", Some(other_ip), 99).is_none());
+        assert!(bans.get_at("anyone", None, 99).is_none());
+
+        bans.ips.insert(ip, Ban { expires_at: None });
+        assert!(bans.get_at("anyone", Some(ip), u64::MAX).is_some());
+    }
+
+    #[test]
+    fn username_ban_applies_from_any_address() {
+        let mut bans = Bans::default();
+        bans.usernames.insert(
+            "player".to_owned(),
+            Ban {
+                expires_at: Some(100),
+            },
+        );
+        assert!(bans
+            .get_at("player", Some("192.0.2.7".parse().unwrap()), 99)
+            .is_some());
+        assert!(bans.get_at("player", None, 99).is_some());
+        assert!(bans.get_at("other", None, 99).is_none());
+    }
+
+    #[test]
+    fn ban_expiry() {
+        assert!(Ban { expires_at: None }.is_active(u64::MAX));
+        let ban = Ban {
+            expires_at: Some(100),
+        };
+        assert!(ban.is_active(99));
+        assert!(!ban.is_active(100));
+    }
+}
@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// File the server configuration is read from
+const CONFIG_PATH: &str = "server.toml";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    TomlDe(#[from] toml::de::Error),
+}
+
+/// TLS certificate of the game server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CertificateConfig {
+    /// PEM certificate chain, generated along with the key if either file is missing
+    pub cert_file: String,
+    /// PEM private key
+    pub key_file: String,
+    /// Subject of a generated certificate
+    pub hostname: String,
+}
+
+impl Default for CertificateConfig {
+    fn default() -> Self {
+        Self {
+            cert_file: "server_cert.pem".to_owned(),
+            key_file: "server_key.pem".to_owned(),
+            hostname: "127.0.0.1".to_owned(),
+        }
+    }
+}
+
+/// Local HTTP admin and status API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub enabled: bool,
+    /// Port on the loopback interface
+    pub port: u16,
+    /// Bearer token required by admin endpoints, admin endpoints are disabled when empty
+    pub token: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 6080,
+            token: String::new(),
+        }
+    }
+}
+
+/// Browser overlay of the board for streams
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayConfig {
+    pub enabled: bool,
+    /// Address the overlay page and its event feed are served on
+    pub address: String,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:6081".to_owned(),
+        }
+    }
+}
+
+/// Server settings, defaults are used for anything missing in the config file
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Address the game server listens on
+    pub address: String,
+    /// Password required to join, no password when empty
+    pub password: String,
+    pub certificate: CertificateConfig,
+    pub http: HttpConfig,
+    pub overlay: OverlayConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: "0.0.0.0:6000".to_owned(),
+            password: String::new(),
+            certificate: CertificateConfig::default(),
+            http: HttpConfig::default(),
+            overlay: OverlayConfig::default(),
+        }
+    }
+}
+
+fn load() -> Result<ServerConfig, ConfigError> {
+    let data = std::fs::read_to_string(CONFIG_PATH)?;
+    Ok(toml::from_str(&data)?)
+}
+
+pub fn load_config(mut config: ResMut<ServerConfig>) {
+    if !Path::new(CONFIG_PATH).exists() {
+        return;
+    }
+    match load() {
+        Ok(loaded) => *config = loaded,
+        Err(e) => warn!("Failed to load {} due to error {}", CONFIG_PATH, e),
+    }
+}
@@ -0,0 +1,46 @@
+use std::io::BufRead;
+
+use bevy::prelude::*;
+use tokio::sync::mpsc;
+
+use crate::admin::Admin;
+
+/// Lines typed into the server's stdin
+#[derive(Resource)]
+struct ConsoleInput(mpsc::UnboundedReceiver<String>);
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, read_stdin)
+            .add_systems(Update, handle_commands);
+    }
+}
+
+fn read_stdin(mut commands: Commands) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    commands.insert_resource(ConsoleInput(receiver));
+}
+
+fn handle_commands(mut input: ResMut<ConsoleInput>, mut admin: Admin) {
+    while let Ok(line) = input.0.try_recv() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match admin.execute(&line) {
+            Ok(output) => println!("{}", output),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::Read;
+
+use bevy::prelude::*;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{
+    admin::{Admin, AdminError},
+    config::{HttpConfig, ServerConfig},
+};
+
+/// Largest accepted request body
+const MAX_BODY_SIZE: u64 = 64 * 1024;
+
+/// Local HTTP admin and status API
+#[derive(Resource)]
+struct HttpApi(tiny_http::Server);
+
+pub struct HttpPlugin;
+
+impl Plugin for HttpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, start_http)
+            .add_systems(Update, handle_requests.run_if(resource_exists::<HttpApi>()));
+    }
+}
+
+fn start_http(mut commands: Commands, config: Res<ServerConfig>) {
+    if !config.http.enabled {
+        return;
+    }
+    match tiny_http::Server::http(("127.0.0.1", config.http.port)) {
+        Ok(server) => {
+            info!("HTTP API listening on 127.0.0.1:{}", config.http.port);
+            commands.insert_resource(HttpApi(server));
+        }
+        Err(e) => warn!("Failed to start HTTP API due to error {}", e),
+    }
+}
+
+fn handle_requests(http: Res<HttpApi>, config: Res<ServerConfig>, mut admin: Admin) {
+    loop {
+        match http.0.try_recv() {
+            Ok(Some(request)) => handle_request(request, &config.http, &mut admin),
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Failed to receive HTTP request due to error {}", e);
+                break;
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+enum Reply {
+    #[serde(rename = "output")]
+    Output(String),
+    #[serde(rename = "error")]
+    Error(String),
+}
+
+fn json_response(status: u16, body: &impl Serialize) -> Response<std::io::Cursor<Vec<u8>>> {
+    let data = serde_json::to_vec(body).unwrap_or_default();
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_data(data)
+        .with_status_code(status)
+        .with_header(header)
+}
+
+fn error_response(status: u16, message: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    json_response(status, &Reply::Error(message.to_owned()))
+}
+
+fn is_authorized(request: &Request, config: &HttpConfig) -> bool {
+    let expected = format!("Bearer {}", config.token);
+    request
+        .headers()
+        .iter()
+        .any(|header| header.field.equiv("Authorization") && header.value.as_str() == expected)
+}
+
+fn handle_request(mut request: Request, config: &HttpConfig, admin: &mut Admin) {
+    let url = request.url().to_owned();
+    let path = url.split('?').next().unwrap_or_default();
+    let response = match (request.method(), path) {
+        (Method::Get, "/api/status") => json_response(200, &admin.status()),
+        (Method::Get, "/api/clients") => json_response(200, admin.status().clients),
+        (Method::Get, "/api/config") => json_response(200, admin.status().config),
+        (Method::Get, "/api/activity") => json_response(200, admin.status().activity),
+        (Method::Post, path) if path.starts_with("/api/admin/") => {
+            let command = &path["/api/admin/".len()..];
+            if config.token.is_empty() {
+                error_response(403, "admin endpoints are disabled")
+            } else if !is_authorized(&request, config) {
+                error_response(401, "missing or invalid token")
+            } else {
+                let mut argument = String::new();
+                let read = request
+                    .as_reader()
+                    .take(MAX_BODY_SIZE)
+                    .read_to_string(&mut argument);
+                match read {
+                    Ok(_) => match admin.execute(&format!("{} {}", command, argument)) {
+                        Ok(output) => json_response(200, &Reply::Output(output)),
+                        Err(e @ AdminError::UnknownCommand(_)) => {
+                            error_response(404, &e.to_string())
+                        }
+                        Err(e) => error_response(400, &e.to_string()),
+                    },
+                    Err(e) => error_response(400, &e.to_string()),
+                }
+            }
+        }
+        _ => error_response(404, "not found"),
+    };
+    if let Err(e) = request.respond(response) {
+        warn!("Failed to send HTTP response due to error {}", e);
+    }
+}
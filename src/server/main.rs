@@ -1,6 +1,21 @@
+mod access;
+mod admin;
+mod bans;
+mod config;
+mod console;
+mod http;
+mod overlay;
+mod peers;
+mod persistence;
+mod proofs;
+mod rate_limit;
+mod replay;
+mod shutdown;
+mod teams;
+
 use std::collections::HashMap;
 
-use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*};
+use bevy::{app::ScheduleRunnerPlugin, prelude::*};
 use bevy_quinnet::{
     server::{
         certificate::CertificateRetrievalMode, ConnectionLostEvent, Endpoint, QuinnetServerPlugin,
@@ -9,63 +24,212 @@ use bevy_quinnet::{
     shared::ClientId,
 };
 
+use access::{Access, JoinRequest};
+use bans::Bans;
 use common::{
-    bingo::Board,
-    protocol::{ClientMessage, ClientProps, ServerMessage},
+    bingo::{unix_now, Board, BoardMode, BoardPrompts, ClaimAttribution, FieldClaim},
+    protocol::{ClientMessage, ClientProps, JoinRejection, ServerMessage},
+    replay::ReplayEvent,
+    roles::{CoHostPermissions, Permission},
+    teams::{Team, TeamSettings, TeamStyles},
     BoardRes,
 };
-
-use common::bingo::GameMode;
+use config::ServerConfig;
+use console::ConsolePlugin;
+use http::HttpPlugin;
+use overlay::OverlayPlugin;
+use peers::{Peers, PeersPlugin};
+use persistence::{ReservedSessions, SnapshotTimer};
+use proofs::Proofs;
+use rate_limit::RateLimiter;
+use replay::ReplayRecorder;
+use shutdown::ShutdownPlugin;
 
 #[derive(Resource, Debug, Clone, Default)]
 struct Clients {
     data: HashMap<ClientId, ClientProps>,
+    /// Joined clients, earliest first, the first one takes over when the host leaves
+    join_order: Vec<ClientId>,
+    co_host_permissions: CoHostPermissions,
+}
+
+impl Clients {
+    /// Whether the client may do the action, every permission check on the server goes through here
+    fn authorize(&self, client_id: ClientId, permission: Permission) -> bool {
+        let allowed = self
+            .data
+            .get(&client_id)
+            .is_some_and(|client| client.can(permission, &self.co_host_permissions));
+        if !allowed {
+            warn!("Client {} is not allowed to {:?}", client_id, permission);
+        }
+        allowed
+    }
+
+    /// Whether the client may make the claim, only players of the team can claim fields on the
+    /// board
+    fn authorize_claim(&self, client_id: ClientId, board: &Board, claim: FieldClaim) -> bool {
+        if !self.authorize(client_id, Permission::ClaimFields) {
+            return false;
+        }
+        let own_team = self
+            .data
+            .get(&client_id)
+            .is_some_and(|client| client.team == Some(claim.team));
+        if !own_team {
+            warn!("Client {} is not in team {}", client_id, claim.team);
+            return false;
+        }
+        let on_board = board.config.prompts.contains(claim.x, claim.y);
+        if !on_board {
+            warn!(
+                "Client {} claimed field {}, {} outside the board",
+                client_id, claim.x, claim.y
+            );
+        }
+        on_board
+    }
 }
 
 fn broadcast(endpoint: &Endpoint, clients: &Clients, msg: ServerMessage) {
     endpoint.try_send_group_message(clients.data.keys(), msg);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_messages(
     mut server: ResMut<Server>,
     mut clients: ResMut<Clients>,
     mut board: ResMut<BoardRes>,
+    mut proofs: ResMut<Proofs>,
+    mut rate_limiter: ResMut<RateLimiter>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut sessions: ResMut<ReservedSessions>,
+    mut access: ResMut<Access>,
+    mut bans: ResMut<Bans>,
+    config: Res<ServerConfig>,
+    peers: Res<Peers>,
 ) {
     let endpoint = server.endpoint_mut();
     for client_id in endpoint.clients() {
         while let Some(message) = endpoint.try_receive_message_from::<ClientMessage>(client_id) {
-            handle_single_message(&mut board, endpoint, &mut clients, message, client_id);
+            if access.is_rejected(client_id) {
+                continue;
+            }
+            let joined = clients.data.contains_key(&client_id);
+            // Repeated joins of joined clients are ignored later on
+            if let (
+                false,
+                ClientMessage::Join {
+                    name,
+                    password,
+                    room_password,
+                },
+            ) = (joined, &message)
+            {
+                let request = JoinRequest {
+                    name,
+                    ip: peers.ip(client_id),
+                    password: password.as_deref(),
+                    room_password: room_password.as_deref(),
+                };
+                let result = match rate_limiter.can_try_join(request.ip) {
+                    true => access.check_join(&config, &bans, &clients, &request),
+                    false => Err(JoinRejection::TooManyAttempts),
+                };
+                if let Err(reason) = result {
+                    info!("Rejected join of {}: {}", name, reason);
+                    if let JoinRejection::WrongServerPassword | JoinRejection::WrongRoomPassword =
+                        reason
+                    {
+                        rate_limiter.record_rejected_join(request.ip);
+                    }
+                    access.reject(endpoint, client_id, reason);
+                    break;
+                }
+            }
+            // Proof chunks come in bursts, so they are limited by size instead
+            if let ClientMessage::ProofChunk { claim, data } = &message {
+                if !rate_limiter.try_acquire_proof_bytes(client_id, data.len()) {
+                    warn!("Rejected proof chunk from client {}", client_id);
+                    // The rest of the proof would be incomplete anyway
+                    proofs.cancel_upload(claim, client_id);
+                    continue;
+                }
+            } else if !rate_limiter.try_acquire(client_id) {
+                warn!("Rejected message from client {}: {:?}", client_id, message);
+                if let ClientMessage::UpdateActivity { .. } = message {
+                    // Revert the optimistic update on the client
+                    endpoint.try_send_message(
+                        client_id,
+                        ServerMessage::SetActivity(board.activity.clone()),
+                    );
+                }
+                continue;
+            }
+            handle_single_message(
+                &mut board,
+                endpoint,
+                &mut clients,
+                &mut proofs,
+                &mut recorder,
+                &mut sessions,
+                &mut access,
+                &mut bans,
+                &peers,
+                message,
+                client_id,
+            );
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_single_message(
     board: &mut Board,
     endpoint: &mut Endpoint,
     clients: &mut Clients,
+    proofs: &mut Proofs,
+    recorder: &mut ReplayRecorder,
+    sessions: &mut ReservedSessions,
+    access: &mut Access,
+    bans: &mut Bans,
+    peers: &Peers,
     message: ClientMessage,
     client_id: ClientId,
 ) {
     match message {
-        ClientMessage::Join { name: username } => {
+        ClientMessage::Join { name: username, .. } => {
             if clients.data.contains_key(&client_id) {
                 return;
             }
 
-            if clients.data.values().any(|x| x.username == username) {
-                endpoint.disconnect_client(client_id).unwrap();
-                return;
-            }
-
             let is_host = clients.data.is_empty();
+            let session = sessions.take(&username);
+            let team = session.as_ref().and_then(|s| s.team).filter(|team| {
+                teams::can_join(clients, &board.config.teams, client_id, Some(*team))
+            });
             clients.data.insert(
                 client_id,
                 ClientProps {
                     is_host,
+                    is_referee: false,
+                    is_co_host: false,
+                    is_caster: false,
                     username: username.clone(),
-                    team: None,
+                    team,
                 },
             );
+            clients.join_order.push(client_id);
+            recorder.record(ReplayEvent::Join {
+                client_id,
+                username,
+            });
+            if let Some(team) = team {
+                recorder.record(ReplayEvent::ChangeTeam {
+                    client_id,
+                    team: Some(team),
+                });
+            }
             endpoint
                 .send_message(client_id, ServerMessage::InitClient(client_id))
                 .unwrap();
@@ -84,6 +248,24 @@ fn handle_single_message(
                     ServerMessage::SetActivity(board.activity.clone()),
                 )
                 .unwrap();
+            endpoint
+                .send_message(
+                    client_id,
+                    ServerMessage::SetCoHostPermissions(clients.co_host_permissions),
+                )
+                .unwrap();
+            endpoint
+                .send_message(
+                    client_id,
+                    ServerMessage::SetTeamSettings(board.config.teams),
+                )
+                .unwrap();
+            endpoint
+                .send_message(
+                    client_id,
+                    ServerMessage::SetTeamStyles(board.config.team_styles.clone()),
+                )
+                .unwrap();
             broadcast(
                 endpoint,
                 clients,
@@ -92,11 +274,45 @@ fn handle_single_message(
         }
         ClientMessage::Disconnect {} => {
             endpoint.disconnect_client(client_id).unwrap();
-            handle_disconnect(endpoint, clients, client_id);
+            handle_disconnect(endpoint, clients, recorder, client_id);
         }
         ClientMessage::ChangeTeam(new_team) => {
-            let client = clients.data.get_mut(&client_id).unwrap();
-            client.team = new_team;
+            if !teams::can_join(clients, &board.config.teams, client_id, new_team) {
+                // Revert the optimistic update on the client
+                endpoint
+                    .try_send_message(client_id, ServerMessage::SetClients(clients.data.clone()));
+                return;
+            }
+            change_team(endpoint, clients, recorder, client_id, new_team);
+        }
+        ClientMessage::JoinRandomTeam => {
+            let Some(team) = teams::random_team(clients, &board.config.teams) else {
+                return;
+            };
+            change_team(endpoint, clients, recorder, client_id, Some(team));
+        }
+        ClientMessage::SetTeamSettings(settings) => {
+            if !clients.authorize(client_id, Permission::ManageTeams) {
+                return;
+            }
+            set_team_settings(board, endpoint, clients, recorder, settings);
+        }
+        ClientMessage::SetTeamStyles(styles) => {
+            if !clients.authorize(client_id, Permission::ManageTeams) {
+                return;
+            }
+            set_team_styles(board, endpoint, clients, styles);
+        }
+        ClientMessage::BalanceTeams => {
+            if !clients.authorize(client_id, Permission::ManageTeams) {
+                return;
+            }
+            for id in teams::balance(clients, &board.config.teams) {
+                recorder.record(ReplayEvent::ChangeTeam {
+                    client_id: id,
+                    team: clients.data[&id].team,
+                });
+            }
             broadcast(
                 endpoint,
                 clients,
@@ -109,17 +325,30 @@ fn handle_single_message(
             y,
             is_active,
         } => {
-            let mode = board.config.mode.game_mode;
-            let win = board.check_win();
-            let activity = board.activity_mut(x, y);
+            if !clients.authorize_claim(client_id, board, FieldClaim { team, x, y }) {
+                return;
+            }
             match is_active {
                 true => {
-                    if (mode != GameMode::Lockout || activity.is_empty()) && win.is_none() {
-                        activity.insert(team);
+                    if board.claim(x, y, team) {
+                        let username = clients.data[&client_id].username.clone();
+                        let attribution = ClaimAttribution {
+                            claim: FieldClaim { team, x, y },
+                            client_id,
+                            username,
+                            timestamp: unix_now(),
+                        };
+                        board.activity.attribute(attribution.clone());
+                        recorder.record(ReplayEvent::Claim(attribution));
+                        recorder.check_win(board);
                     }
                 }
                 false => {
-                    activity.remove(&team);
+                    let claim = FieldClaim { team, x, y };
+                    if board.unclaim(x, y, &team) {
+                        proofs.remove(&claim);
+                        recorder.record(ReplayEvent::Unclaim(claim));
+                    }
                 }
             };
 
@@ -130,57 +359,330 @@ fn handle_single_message(
             );
         }
         ClientMessage::SetMode(mode) => {
-            let client = clients.data.get_mut(&client_id).unwrap();
-            if !client.is_host {
+            if !clients.authorize(client_id, Permission::EditBoard) {
                 return;
             }
-            board.config.mode = mode.clone();
-            board.reset_activity();
-            broadcast(endpoint, clients, ServerMessage::SetMode(mode));
+            set_mode(board, endpoint, clients, proofs, recorder, mode);
         }
         ClientMessage::SetPrompts(prompts) => {
-            let client = clients.data.get_mut(&client_id).unwrap();
-            if !client.is_host {
+            if !clients.authorize(client_id, Permission::EditBoard) {
+                return;
+            }
+            if !prompts.is_valid() {
+                warn!("Rejected invalid prompts from client {}", client_id);
                 return;
             }
-            board.config.prompts = prompts.clone();
-            board.reset_activity();
-            broadcast(endpoint, clients, ServerMessage::SetPrompts(prompts));
+            set_prompts(board, endpoint, clients, proofs, recorder, prompts);
+        }
+        ClientMessage::SetRoomPassword(password) => {
+            if !clients.authorize(client_id, Permission::SetRoomPassword) {
+                return;
+            }
+            access.set_room_password(password);
         }
         ClientMessage::ResetActivity => {
-            let client = clients.data.get_mut(&client_id).unwrap();
-            if !client.is_host {
+            if !clients.authorize(client_id, Permission::ResetBoard) {
+                return;
+            }
+            reset_board(board, endpoint, clients, proofs, recorder);
+        }
+        ClientMessage::SetRole {
+            client_id: target_id,
+            role,
+            granted,
+        } => {
+            if !clients.authorize(client_id, Permission::ManageRoles) {
+                return;
+            }
+            let Some(target) = clients.data.get_mut(&target_id) else {
+                return;
+            };
+            if !target.set_role(role, granted) {
                 return;
             }
-            board.reset_activity();
             broadcast(
                 endpoint,
                 clients,
-                ServerMessage::SetActivity(board.activity.clone()),
+                ServerMessage::SetClients(clients.data.clone()),
             );
         }
-        ClientMessage::Kick(client_id) => {
-            endpoint.try_disconnect_client(client_id);
-            handle_disconnect(endpoint, clients, client_id);
+        ClientMessage::SetCoHostPermissions(permissions) => {
+            if !clients.authorize(client_id, Permission::ManageRoles) {
+                return;
+            }
+            clients.co_host_permissions = permissions;
+            broadcast(
+                endpoint,
+                clients,
+                ServerMessage::SetCoHostPermissions(permissions),
+            );
+        }
+        ClientMessage::TransferHost(target_id) => {
+            if !clients.authorize(client_id, Permission::TransferHost) {
+                return;
+            }
+            transfer_host(endpoint, clients, target_id);
+        }
+        ClientMessage::ReviewClaim { claim, approve } => {
+            if !clients.authorize(client_id, Permission::ReviewClaims) {
+                return;
+            }
+            // Rejecting is fine, but the team's own claims need an impartial approval
+            let own_team = clients.data[&client_id].team == Some(claim.team);
+            if approve && own_team {
+                warn!(
+                    "Client {} may not approve claims of its own team",
+                    client_id
+                );
+                return;
+            }
+            if board.review_claim(claim, approve) {
+                if !approve {
+                    proofs.remove(&claim);
+                }
+                recorder.record(ReplayEvent::ReviewClaim { claim, approve });
+                recorder.check_win(board);
+                broadcast(
+                    endpoint,
+                    clients,
+                    ServerMessage::SetActivity(board.activity.clone()),
+                );
+            }
+        }
+        ClientMessage::AttachProof { claim, header } => {
+            if !clients.authorize_claim(client_id, board, claim) {
+                return;
+            }
+            let is_claimed = board.is_active(claim.x, claim.y, &claim.team)
+                || board.is_pending(claim.x, claim.y, &claim.team);
+            if !is_claimed || !proofs.start(claim, client_id, header) {
+                warn!("Rejected proof from client {}", client_id);
+            }
+        }
+        ClientMessage::ProofChunk { claim, data } => {
+            proofs.push_chunk(claim, client_id, &data);
+        }
+        ClientMessage::RequestProof(claim) => {
+            if !clients.authorize(client_id, Permission::InspectProofs) {
+                return;
+            }
+            let proof = proofs.get(&claim);
+            endpoint.try_send_message(
+                client_id,
+                ServerMessage::ProofHeader {
+                    claim,
+                    header: proof.map(|proof| proof.header.clone()),
+                },
+            );
+            for chunk in proof.into_iter().flat_map(|proof| proof.chunks()) {
+                endpoint.try_send_message(
+                    client_id,
+                    ServerMessage::ProofChunk {
+                        claim,
+                        data: chunk.to_vec(),
+                    },
+                );
+            }
+        }
+        ClientMessage::Kick(target_id) => {
+            let target_is_host = clients.data.get(&target_id).is_some_and(|t| t.is_host);
+            if target_is_host || !clients.authorize(client_id, Permission::Kick) {
+                return;
+            }
+            kick(endpoint, clients, recorder, target_id);
+        }
+        ClientMessage::Ban {
+            client_id: target_id,
+            duration_secs,
+            ban_ip,
+        } => {
+            if target_id == client_id || !clients.authorize(client_id, Permission::Ban) {
+                return;
+            }
+            let Some(target) = clients.data.get(&target_id) else {
+                return;
+            };
+            info!(
+                "{} banned {}",
+                clients.data[&client_id].username, target.username
+            );
+            let ip = peers.ip(target_id).filter(|_| ban_ip);
+            bans.ban(Some(target.username.clone()), ip, duration_secs);
+            kick(endpoint, clients, recorder, target_id);
+        }
+    }
+}
+
+fn set_mode(
+    board: &mut Board,
+    endpoint: &mut Endpoint,
+    clients: &Clients,
+    proofs: &mut Proofs,
+    recorder: &mut ReplayRecorder,
+    mode: BoardMode,
+) {
+    board.config.mode = mode.clone();
+    board.reset_activity();
+    proofs.clear();
+    recorder.record(ReplayEvent::SetMode(mode.clone()));
+    broadcast(endpoint, clients, ServerMessage::SetMode(mode));
+}
+
+fn set_prompts(
+    board: &mut Board,
+    endpoint: &mut Endpoint,
+    clients: &Clients,
+    proofs: &mut Proofs,
+    recorder: &mut ReplayRecorder,
+    prompts: BoardPrompts,
+) {
+    board.config.prompts = prompts.clone();
+    board.reset_activity();
+    proofs.clear();
+    recorder.record(ReplayEvent::SetPrompts(prompts.clone()));
+    broadcast(endpoint, clients, ServerMessage::SetPrompts(prompts));
+}
+
+fn set_team_settings(
+    board: &mut Board,
+    endpoint: &mut Endpoint,
+    clients: &mut Clients,
+    recorder: &mut ReplayRecorder,
+    settings: TeamSettings,
+) {
+    let settings = settings.sanitized();
+    board.config.teams = settings;
+    broadcast(endpoint, clients, ServerMessage::SetTeamSettings(settings));
+    // Players of removed teams become spectators
+    for (id, client) in clients.data.iter_mut() {
+        if client.team.is_some_and(|team| !settings.is_active(team)) {
+            client.team = None;
+            recorder.record(ReplayEvent::ChangeTeam {
+                client_id: *id,
+                team: None,
+            });
         }
     }
+    broadcast(
+        endpoint,
+        clients,
+        ServerMessage::SetClients(clients.data.clone()),
+    );
+}
+
+fn set_team_styles(
+    board: &mut Board,
+    endpoint: &mut Endpoint,
+    clients: &Clients,
+    styles: TeamStyles,
+) {
+    board.config.team_styles = styles.sanitized();
+    broadcast(
+        endpoint,
+        clients,
+        ServerMessage::SetTeamStyles(board.config.team_styles.clone()),
+    );
+}
+
+/// Clear all claims and start recording a new match
+fn reset_board(
+    board: &mut Board,
+    endpoint: &mut Endpoint,
+    clients: &Clients,
+    proofs: &mut Proofs,
+    recorder: &mut ReplayRecorder,
+) {
+    board.reset_activity();
+    proofs.clear();
+    recorder.start(board, &clients.data);
+    broadcast(
+        endpoint,
+        clients,
+        ServerMessage::SetActivity(board.activity.clone()),
+    );
+}
+
+fn change_team(
+    endpoint: &mut Endpoint,
+    clients: &mut Clients,
+    recorder: &mut ReplayRecorder,
+    client_id: ClientId,
+    team: Option<Team>,
+) {
+    let Some(client) = clients.data.get_mut(&client_id) else {
+        return;
+    };
+    client.team = team;
+    recorder.record(ReplayEvent::ChangeTeam { client_id, team });
+    broadcast(
+        endpoint,
+        clients,
+        ServerMessage::SetClients(clients.data.clone()),
+    );
+}
+
+fn kick(
+    endpoint: &mut Endpoint,
+    clients: &mut Clients,
+    recorder: &mut ReplayRecorder,
+    client_id: ClientId,
+) {
+    endpoint.try_disconnect_client(client_id);
+    handle_disconnect(endpoint, clients, recorder, client_id);
+}
+
+/// Make the client the only host, returns false if there is no such client
+fn transfer_host(endpoint: &mut Endpoint, clients: &mut Clients, client_id: ClientId) -> bool {
+    if !clients.data.contains_key(&client_id) {
+        return false;
+    }
+    for (id, client) in clients.data.iter_mut() {
+        client.is_host = *id == client_id;
+        if client.is_host {
+            client.is_co_host = false;
+        }
+    }
+    broadcast(
+        endpoint,
+        clients,
+        ServerMessage::SetClients(clients.data.clone()),
+    );
+    true
 }
 
 fn handle_connection_lost(
     mut connection_lost_events: EventReader<ConnectionLostEvent>,
     mut server: ResMut<Server>,
     mut clients: ResMut<Clients>,
+    mut rate_limiter: ResMut<RateLimiter>,
+    mut recorder: ResMut<ReplayRecorder>,
 ) {
     for client in connection_lost_events.read() {
-        handle_disconnect(server.endpoint_mut(), &mut clients, client.id);
+        rate_limiter.remove(client.id);
+        handle_disconnect(
+            server.endpoint_mut(),
+            &mut clients,
+            &mut recorder,
+            client.id,
+        );
     }
 }
 
-fn handle_disconnect(endpoint: &mut Endpoint, clients: &mut Clients, client_id: ClientId) {
+fn handle_disconnect(
+    endpoint: &mut Endpoint,
+    clients: &mut Clients,
+    recorder: &mut ReplayRecorder,
+    client_id: ClientId,
+) {
     if let Some(client) = clients.data.remove(&client_id) {
+        clients.join_order.retain(|id| *id != client_id);
+        recorder.record(ReplayEvent::Leave { client_id });
         if client.is_host {
-            if let Some(client) = clients.data.iter_mut().next() {
-                client.1.is_host = true;
+            if let Some(successor) = clients.join_order.first().copied() {
+                let successor = clients.data.get_mut(&successor).unwrap();
+                successor.is_host = true;
+                successor.is_co_host = false;
+                info!("{} is the new host", successor.username);
             }
         }
         broadcast(
@@ -191,27 +693,69 @@ fn handle_disconnect(endpoint: &mut Endpoint, clients: &mut Clients, client_id:
     }
 }
 
-fn start_listening(mut server: ResMut<Server>) {
-    server
+fn start_listening(mut server: ResMut<Server>, config: Res<ServerConfig>) {
+    let certificate = &config.certificate;
+    let (certificate, _) = server
         .start_endpoint(
-            ServerConfiguration::from_string("0.0.0.0:6000").unwrap(),
-            CertificateRetrievalMode::GenerateSelfSigned {
-                server_hostname: "127.0.0.1".to_string(),
+            ServerConfiguration::from_string(&config.address).unwrap(),
+            CertificateRetrievalMode::LoadFromFileOrGenerateSelfSigned {
+                cert_file: certificate.cert_file.clone(),
+                key_file: certificate.key_file.clone(),
+                save_on_disk: true,
+                server_hostname: certificate.hostname.clone(),
             },
         )
         .unwrap();
+    // Players can compare it with the one their client trusted on first connection
+    info!("Certificate fingerprint {}", certificate.fingerprint);
+}
+
+fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    board: Res<BoardRes>,
+    clients: Res<Clients>,
+) {
+    recorder.start(&board, &clients.data);
 }
 
 fn main() {
     App::new()
         .add_plugins((
             ScheduleRunnerPlugin::default(),
-            LogPlugin::default(),
+            PeersPlugin,
             QuinnetServerPlugin::default(),
+            ShutdownPlugin,
+            ConsolePlugin,
+            HttpPlugin,
+            OverlayPlugin,
         ))
+        .init_resource::<ServerConfig>()
+        .init_resource::<Bans>()
         .insert_resource(Clients::default())
         .insert_resource(BoardRes::default())
-        .add_systems(Startup, start_listening)
-        .add_systems(Update, (handle_messages, handle_connection_lost))
+        .init_resource::<ReservedSessions>()
+        .init_resource::<Access>()
+        .init_resource::<Proofs>()
+        .init_resource::<RateLimiter>()
+        .init_resource::<ReplayRecorder>()
+        .init_resource::<SnapshotTimer>()
+        .add_systems(PreStartup, (config::load_config, bans::load_bans))
+        .add_systems(
+            Startup,
+            (
+                start_listening,
+                (persistence::restore, start_recording).chain(),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_messages,
+                handle_connection_lost,
+                access::disconnect_rejected,
+                persistence::save_periodically,
+                peers::forget_disconnected,
+            ),
+        )
         .run();
 }
@@ -0,0 +1,256 @@
+use std::{
+    io::Write,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response};
+
+use common::{
+    bingo::{Board, BoardConfig},
+    teams::{MarkerLayout, Team, TeamStyles},
+    BoardRes,
+};
+
+use crate::config::ServerConfig;
+
+/// Self-contained page rendering the board from the event feed
+const OVERLAY_PAGE: &str = include_str!("overlay.html");
+/// Time between comments keeping idle feeds open
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Activity of a single field as sent to the overlay
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct FieldState {
+    teams: Vec<Team>,
+    pending: Vec<Team>,
+}
+
+#[derive(Serialize)]
+struct TeamColor {
+    team: Team,
+    name: String,
+    color: String,
+}
+
+#[derive(Serialize)]
+struct FullState<'a> {
+    /// In marker order
+    teams: Vec<TeamColor>,
+    markers: MarkerLayout,
+    config: &'a BoardConfig,
+    fields: &'a [FieldState],
+    winner: Option<Team>,
+}
+
+#[derive(Serialize)]
+struct FieldChange<'a> {
+    offset: usize,
+    #[serde(flatten)]
+    state: &'a FieldState,
+}
+
+#[derive(Serialize)]
+struct ActivityDelta<'a> {
+    changes: Vec<FieldChange<'a>>,
+    winner: Option<Team>,
+}
+
+/// Overlay server and the feeds of connected pages
+#[derive(Resource)]
+struct Overlay {
+    server: tiny_http::Server,
+    /// Each feed is written by its own thread, so a slow page can't stall the server
+    feeds: Vec<mpsc::Sender<String>>,
+    config: Option<BoardConfig>,
+    /// Teams that get a marker slot
+    shown_teams: Vec<Team>,
+    fields: Vec<FieldState>,
+    winner: Option<Team>,
+    last_keep_alive: Instant,
+}
+
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, start_overlay).add_systems(
+            Update,
+            (handle_requests, send_updates).run_if(resource_exists::<Overlay>()),
+        );
+    }
+}
+
+fn start_overlay(mut commands: Commands, config: Res<ServerConfig>) {
+    if !config.overlay.enabled {
+        return;
+    }
+    match tiny_http::Server::http(&config.overlay.address) {
+        Ok(server) => {
+            info!("Stream overlay on http://{}/", config.overlay.address);
+            commands.insert_resource(Overlay {
+                server,
+                feeds: Vec::new(),
+                config: None,
+                shown_teams: Vec::new(),
+                fields: Vec::new(),
+                winner: None,
+                last_keep_alive: Instant::now(),
+            });
+        }
+        Err(e) => warn!("Failed to start stream overlay due to error {}", e),
+    }
+}
+
+fn team_colors(styles: &TeamStyles, teams: &[Team]) -> Vec<TeamColor> {
+    teams
+        .iter()
+        .map(|team| TeamColor {
+            team: *team,
+            name: styles.name(*team),
+            color: styles.hex_color(*team),
+        })
+        .collect()
+}
+
+fn field_states(board: &Board) -> Vec<FieldState> {
+    let mut fields = Vec::new();
+    for x in 0..board.config.prompts.x_size {
+        for y in 0..board.config.prompts.y_size {
+            let teams = Team::iter().filter(|team| board.is_active(x, y, team));
+            let pending = Team::iter().filter(|team| board.is_pending(x, y, team));
+            fields.push(FieldState {
+                teams: teams.copied().collect(),
+                pending: pending.copied().collect(),
+            });
+        }
+    }
+    fields
+}
+
+/// Server-sent event with a JSON payload
+fn event(name: &str, data: &impl Serialize) -> String {
+    let data = serde_json::to_string(data).unwrap_or_default();
+    format!("event: {}\ndata: {}\n\n", name, data)
+}
+
+impl Overlay {
+    fn full_state(&self) -> String {
+        let Some(config) = &self.config else {
+            return String::new();
+        };
+        event(
+            "state",
+            &FullState {
+                teams: team_colors(&config.team_styles, &self.shown_teams),
+                markers: MarkerLayout::new(self.shown_teams.len()),
+                config,
+                fields: &self.fields,
+                winner: self.winner,
+            },
+        )
+    }
+
+    fn broadcast(&mut self, message: &str) {
+        self.feeds
+            .retain(|feed| feed.send(message.to_owned()).is_ok());
+    }
+
+    /// Hand the connection over to a thread streaming events to it
+    fn subscribe(&mut self, request: Request) {
+        let (sender, receiver) = mpsc::channel::<String>();
+        let initial = self.full_state();
+        if sender.send(initial).is_err() {
+            return;
+        }
+        let mut writer = request.into_writer();
+        std::thread::spawn(move || {
+            let headers = "HTTP/1.1 200 OK\r\n\
+                Content-Type: text/event-stream\r\n\
+                Cache-Control: no-cache\r\n\
+                Connection: keep-alive\r\n\
+                Access-Control-Allow-Origin: *\r\n\r\n";
+            if writer.write_all(headers.as_bytes()).is_err() {
+                return;
+            }
+            for message in receiver {
+                let written = writer
+                    .write_all(message.as_bytes())
+                    .and_then(|_| writer.flush());
+                if written.is_err() {
+                    break;
+                }
+            }
+        });
+        self.feeds.push(sender);
+    }
+}
+
+fn handle_requests(mut overlay: ResMut<Overlay>) {
+    loop {
+        let request = match overlay.server.try_recv() {
+            Ok(Some(request)) => request,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Failed to receive overlay request due to error {}", e);
+                break;
+            }
+        };
+        let path = request
+            .url()
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        let response = match (request.method(), path.as_str()) {
+            (Method::Get, "/") => {
+                let header = Header::from_bytes("Content-Type", "text/html; charset=utf-8");
+                Response::from_string(OVERLAY_PAGE).with_header(header.unwrap())
+            }
+            (Method::Get, "/events") => {
+                overlay.subscribe(request);
+                continue;
+            }
+            _ => Response::from_string("not found").with_status_code(404),
+        };
+        if let Err(e) = request.respond(response) {
+            warn!("Failed to send overlay response due to error {}", e);
+        }
+    }
+}
+
+/// Send the whole board when its config changes, and changed fields otherwise.
+/// Nothing is rebuilt on ticks that didn't touch the board.
+fn send_updates(mut overlay: ResMut<Overlay>, board: Res<BoardRes>) {
+    if !board.is_changed() {
+        if overlay.last_keep_alive.elapsed() >= KEEP_ALIVE_INTERVAL {
+            overlay.last_keep_alive = Instant::now();
+            overlay.broadcast(": keep-alive\n\n");
+        }
+        return;
+    }
+    let fields = field_states(&board);
+    let winner = board.check_win();
+    let shown_teams = board.shown_teams().collect::<Vec<_>>();
+    if overlay.config.as_ref() != Some(&board.config) || overlay.shown_teams != shown_teams {
+        overlay.config = Some(board.config.clone());
+        overlay.shown_teams = shown_teams;
+        overlay.fields = fields;
+        overlay.winner = winner;
+        let state = overlay.full_state();
+        overlay.broadcast(&state);
+    } else if overlay.fields != fields || overlay.winner != winner {
+        let changes = fields
+            .iter()
+            .enumerate()
+            .filter(|(offset, state)| overlay.fields.get(*offset) != Some(*state))
+            .map(|(offset, state)| FieldChange { offset, state })
+            .collect();
+        let delta = event("activity", &ActivityDelta { changes, winner });
+        overlay.fields = fields;
+        overlay.winner = winner;
+        overlay.broadcast(&delta);
+    }
+}
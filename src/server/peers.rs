@@ -0,0 +1,130 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    log::{Level, LogPlugin},
+    prelude::*,
+    utils::tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    },
+};
+use bevy_quinnet::{server::Server, shared::ClientId};
+use tracing_subscriber::{
+    filter::{EnvFilter, Targets},
+    layer::{Context, SubscriberExt},
+    util::SubscriberInitExt,
+    Layer,
+};
+
+/// Module bevy_quinnet logs new connections from
+const CONNECTION_LOG_TARGET: &str = "bevy_quinnet::server";
+
+/// Addresses of connected clients
+///
+/// bevy_quinnet doesn't expose them, so they are read from its log of new connections.
+#[derive(Resource, Clone, Default)]
+pub struct Peers(Arc<Mutex<HashMap<ClientId, IpAddr>>>);
+
+impl Peers {
+    pub fn ip(&self, client_id: ClientId) -> Option<IpAddr> {
+        self.0.lock().unwrap().get(&client_id).copied()
+    }
+}
+
+/// Drop the addresses of closed connections
+pub fn forget_disconnected(peers: Res<Peers>, server: Res<Server>) {
+    let Some(endpoint) = server.get_endpoint() else {
+        return;
+    };
+    let connected = endpoint.clients();
+    peers
+        .0
+        .lock()
+        .unwrap()
+        .retain(|client_id, _| connected.contains(client_id));
+}
+
+/// Parse a line like `New connection from 127.0.0.1:51234, client_id: 1`
+fn parse_connection_log(message: &str) -> Option<(ClientId, IpAddr)> {
+    let rest = message.strip_prefix("New connection from ")?;
+    let (addr, client_id) = rest.split_once(", client_id: ")?;
+    let addr = addr.parse::<SocketAddr>().ok()?;
+    Some((client_id.trim().parse().ok()?, addr.ip()))
+}
+
+#[derive(Default)]
+struct MessageVisitor(Option<String>);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// Tracing layer recording the address of each new connection
+struct PeersLayer(Peers);
+
+impl<S: Subscriber> Layer<S> for PeersLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        if let Some((client_id, ip)) = visitor.0.as_deref().and_then(parse_connection_log) {
+            self.0 .0.lock().unwrap().insert(client_id, ip);
+        }
+    }
+}
+
+/// Logging like [`LogPlugin`], with a layer filling [`Peers`]
+pub struct PeersPlugin;
+
+impl Plugin for PeersPlugin {
+    fn build(&self, app: &mut App) {
+        let peers = Peers::default();
+        let defaults = LogPlugin::default();
+        let default_filter = format!("{},{}", defaults.level, defaults.filter);
+        let filter = EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(&default_filter))
+            .unwrap();
+        // Filtered on its own, so connections are recorded whatever the log level
+        let peers_filter = Targets::new().with_target(CONNECTION_LOG_TARGET, Level::INFO);
+        let result = tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_filter(filter),
+            )
+            .with(PeersLayer(peers.clone()).with_filter(peers_filter))
+            .try_init();
+        if let Err(e) = result {
+            warn!("Failed to set up logging due to error {}", e);
+        }
+        app.insert_resource(peers);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_connection_logs() {
+        assert_eq!(
+            parse_connection_log("New connection from 127.0.0.1:51234, client_id: 3"),
+            Some((3, "127.0.0.1".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_connection_log("New connection from [::1]:6000, client_id: 12"),
+            Some((12, "::1".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_connection_log("Client 3 connection closed: timed out"),
+            None
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use common::{bingo::Board, roles::CoHostPermissions, teams::Team, BoardRes};
+
+use crate::{
+    access::{Access, PasswordHash},
+    Clients,
+};
+
+/// File the server state is saved to
+const SNAPSHOT_PATH: &str = "server_state.json";
+/// Version of the snapshot format written by this build
+const SNAPSHOT_VERSION: u32 = 3;
+/// Oldest snapshot format that can still be restored, missing fields take their defaults
+const MIN_SNAPSHOT_VERSION: u32 = 1;
+/// Time between periodic snapshots
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(thiserror::Error, Debug)]
+pub enum PersistenceError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("unsupported snapshot version {0}")]
+    UnsupportedVersion(u32),
+    #[error("board size does not match the number of prompts or fields")]
+    InvalidBoard,
+}
+
+/// Player state kept for a username across server restarts
+///
+/// Roles aren't kept, anyone could claim them by joining under the same name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub team: Option<Team>,
+}
+
+/// Sessions of players who have not rejoined since the server restarted
+#[derive(Resource, Debug, Default)]
+pub struct ReservedSessions {
+    data: HashMap<String, Session>,
+}
+
+impl ReservedSessions {
+    /// Claim the session reserved for a username
+    pub fn take(&mut self, username: &str) -> Option<Session> {
+        self.data.remove(username)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    board: Board,
+    sessions: HashMap<String, Session>,
+    /// Plain text room password of version 2 snapshots, only read to hash it
+    #[serde(default, skip_serializing)]
+    room_password: Option<String>,
+    #[serde(default)]
+    room_password_hash: Option<PasswordHash>,
+    #[serde(default)]
+    co_host_permissions: CoHostPermissions,
+}
+
+/// Restore the state of the previous server run from the last snapshot
+pub fn restore(
+    mut board: ResMut<BoardRes>,
+    mut sessions: ResMut<ReservedSessions>,
+    mut clients: ResMut<Clients>,
+    mut access: ResMut<Access>,
+) {
+    if !Path::new(SNAPSHOT_PATH).exists() {
+        return;
+    }
+    match load() {
+        Ok(snapshot) => {
+            info!(
+                "Restored board and {} sessions from {}",
+                snapshot.sessions.len(),
+                SNAPSHOT_PATH
+            );
+            board.board = snapshot.board;
+            // The file may have been edited by hand, like board files loaded by admins
+            board.config.teams = board.config.teams.sanitized();
+            board.config.team_styles = board.config.team_styles.clone().sanitized();
+            sessions.data = snapshot.sessions;
+            clients.co_host_permissions = snapshot.co_host_permissions;
+            match snapshot.room_password_hash {
+                Some(hash) => access.set_room_password_hash(Some(hash)),
+                None => access.set_room_password(snapshot.room_password),
+            }
+        }
+        Err(e) => warn!("Failed to restore server state due to error {}", e),
+    }
+}
+
+fn load() -> Result<Snapshot, PersistenceError> {
+    let data = std::fs::read_to_string(SNAPSHOT_PATH)?;
+    let snapshot: Snapshot = serde_json::from_str(&data)?;
+    if !(MIN_SNAPSHOT_VERSION..=SNAPSHOT_VERSION).contains(&snapshot.version) {
+        return Err(PersistenceError::UnsupportedVersion(snapshot.version));
+    }
+    let board = &snapshot.board;
+    if !board.config.prompts.is_valid()
+        || board.activity.activity.len() != board.config.prompts.prompts.len()
+    {
+        return Err(PersistenceError::InvalidBoard);
+    }
+    Ok(snapshot)
+}
+
+/// Write the current state to disk, replacing the previous snapshot
+pub fn save(
+    board: &Board,
+    clients: &Clients,
+    sessions: &ReservedSessions,
+    access: &Access,
+) -> Result<(), PersistenceError> {
+    let mut all_sessions = sessions.data.clone();
+    for client in clients.data.values() {
+        let session = Session { team: client.team };
+        all_sessions.insert(client.username.clone(), session);
+    }
+    let snapshot = Snapshot {
+        version: SNAPSHOT_VERSION,
+        board: board.clone(),
+        sessions: all_sessions,
+        room_password: None,
+        room_password_hash: access.room_password_hash().cloned(),
+        co_host_permissions: clients.co_host_permissions,
+    };
+    // Write a temporary file first so a crash mid-write keeps the previous snapshot
+    let tmp_path = format!("{}.tmp", SNAPSHOT_PATH);
+    std::fs::write(&tmp_path, serde_json::to_string(&snapshot)?)?;
+    std::fs::rename(tmp_path, SNAPSHOT_PATH)?;
+    Ok(())
+}
+
+/// Time of the last periodic snapshot
+#[derive(Resource)]
+pub struct SnapshotTimer(Instant);
+
+impl Default for SnapshotTimer {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+pub fn save_periodically(
+    mut timer: ResMut<SnapshotTimer>,
+    board: Res<BoardRes>,
+    clients: Res<Clients>,
+    sessions: Res<ReservedSessions>,
+    access: Res<Access>,
+) {
+    if timer.0.elapsed() < SNAPSHOT_INTERVAL {
+        return;
+    }
+    timer.0 = Instant::now();
+    if let Err(e) = save(&board, &clients, &sessions, &access) {
+        warn!("Failed to save server state due to error {}", e);
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_quinnet::shared::ClientId;
+
+use common::{
+    bingo::FieldClaim,
+    proof::{Proof, ProofHeader},
+};
+
+/// Proofs a client can be uploading at once
+const MAX_UPLOADS_PER_CLIENT: usize = 2;
+
+struct StoredProof {
+    submitter: ClientId,
+    proof: Proof,
+}
+
+/// Proofs attached to claims
+#[derive(Resource, Default)]
+pub struct Proofs {
+    data: HashMap<FieldClaim, StoredProof>,
+}
+
+impl Proofs {
+    /// Start receiving a proof, replacing the previous one, returns false if it is invalid or
+    /// the submitter is uploading too many proofs already
+    pub fn start(&mut self, claim: FieldClaim, submitter: ClientId, header: ProofHeader) -> bool {
+        if !header.is_valid() {
+            return false;
+        }
+        let uploads = self
+            .data
+            .iter()
+            .filter(|(other, stored)| {
+                **other != claim && stored.submitter == submitter && !stored.proof.is_complete()
+            })
+            .count();
+        if uploads >= MAX_UPLOADS_PER_CLIENT {
+            return false;
+        }
+        let proof = Proof::new(header);
+        self.data.insert(claim, StoredProof { submitter, proof });
+        true
+    }
+
+    /// Append an image chunk to a proof being received from the submitter
+    pub fn push_chunk(&mut self, claim: FieldClaim, submitter: ClientId, data: &[u8]) {
+        let Some(stored) = self.data.get_mut(&claim) else {
+            return;
+        };
+        if stored.submitter != submitter {
+            return;
+        }
+        if !stored.proof.push_chunk(data) {
+            warn!("Discarded oversized proof from client {}", submitter);
+            self.data.remove(&claim);
+        }
+    }
+
+    /// Fully received proof of a claim
+    pub fn get(&self, claim: &FieldClaim) -> Option<&Proof> {
+        self.data
+            .get(claim)
+            .map(|stored| &stored.proof)
+            .filter(|proof| proof.is_complete())
+    }
+
+    /// Drop a proof the submitter is still uploading
+    pub fn cancel_upload(&mut self, claim: &FieldClaim, submitter: ClientId) {
+        let uploading = self
+            .data
+            .get(claim)
+            .is_some_and(|stored| stored.submitter == submitter && !stored.proof.is_complete());
+        if uploading {
+            self.data.remove(claim);
+        }
+    }
+
+    pub fn remove(&mut self, claim: &FieldClaim) {
+        self.data.remove(claim);
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::teams::Team;
+
+    use super::*;
+
+    fn claim(x: u8) -> FieldClaim {
+        FieldClaim {
+            team: Team::new(0).unwrap(),
+            x,
+            y: 0,
+        }
+    }
+
+    fn header(image_size: u32) -> ProofHeader {
+        ProofHeader {
+            note: String::new(),
+            image_name: Some("proof.png".to_owned()),
+            image_size,
+        }
+    }
+
+    #[test]
+    fn uploads_are_limited_per_client() {
+        let mut proofs = Proofs::default();
+        for x in 0..MAX_UPLOADS_PER_CLIENT as u8 {
+            assert!(proofs.start(claim(x), 1, header(10)));
+        }
+        let next = claim(MAX_UPLOADS_PER_CLIENT as u8);
+        assert!(!proofs.start(next, 1, header(10)));
+        assert!(proofs.start(next, 2, header(10)));
+        // Replacing a proof doesn't count as another upload
+        assert!(proofs.start(claim(0), 1, header(10)));
+        // Finished uploads free their slot
+        proofs.push_chunk(claim(0), 1, &[0; 10]);
+        assert!(proofs.get(&claim(0)).is_some());
+        assert!(proofs.start(claim(5), 1, header(10)));
+    }
+
+    #[test]
+    fn cancel_keeps_finished_proofs() {
+        let mut proofs = Proofs::default();
+        proofs.start(claim(0), 1, header(10));
+        proofs.start(claim(1), 1, header(10));
+        proofs.push_chunk(claim(0), 1, &[0; 10]);
+        proofs.cancel_upload(&claim(0), 1);
+        proofs.cancel_upload(&claim(1), 1);
+        assert!(proofs.get(&claim(0)).is_some());
+        assert!(!proofs.data.contains_key(&claim(1)));
+    }
+}
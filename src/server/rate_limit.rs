@@ -0,0 +1,155 @@
+use std::{collections::HashMap, net::IpAddr, time::Instant};
+
+use bevy::prelude::*;
+use bevy_quinnet::shared::ClientId;
+
+use common::proof::MAX_PROOF_IMAGE_SIZE;
+
+/// Maximum burst of messages a client can send at once
+const BURST_SIZE: f32 = 20.0;
+/// Messages per second a client can send continuously
+const MESSAGES_PER_SEC: f32 = 10.0;
+/// Proof bytes a client can send at once, enough for one full proof
+const PROOF_BYTES_BURST: f32 = MAX_PROOF_IMAGE_SIZE as f32;
+/// Proof bytes per second a client can send continuously
+const PROOF_BYTES_PER_SEC: f32 = 256.0 * 1024.0;
+/// Wrong passwords allowed at once from one address
+const REJECTED_JOIN_BURST: f32 = 5.0;
+/// Wrong passwords per second allowed continuously from one address, slows down password guessing
+const REJECTED_JOINS_PER_SEC: f32 = 0.2;
+
+/// Token bucket of a single client
+struct Bucket {
+    tokens: f32,
+    last_refill: Instant,
+    size: f32,
+    per_sec: f32,
+}
+
+impl Bucket {
+    fn full(size: f32, per_sec: f32) -> Self {
+        Self {
+            tokens: size,
+            last_refill: Instant::now(),
+            size,
+            per_sec,
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f32();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.size);
+        self.last_refill = now;
+    }
+
+    fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.size
+    }
+
+    fn has_token(&mut self) -> bool {
+        self.refill();
+        self.tokens >= 1.0
+    }
+
+    fn try_take(&mut self) -> bool {
+        self.try_take_many(1.0)
+    }
+
+    fn try_take_many(&mut self, amount: f32) -> bool {
+        self.refill();
+        if self.tokens < amount {
+            return false;
+        }
+        self.tokens -= amount;
+        true
+    }
+}
+
+/// Per-client limit of incoming messages
+#[derive(Resource, Default)]
+pub struct RateLimiter {
+    buckets: HashMap<ClientId, Bucket>,
+    /// Proof chunks are limited by size rather than count
+    proof_buckets: HashMap<ClientId, Bucket>,
+    /// Rejected clients are disconnected, so their rejections are limited by address across
+    /// connections, clients with an unknown address share one bucket
+    rejected_joins: HashMap<Option<IpAddr>, Bucket>,
+}
+
+impl RateLimiter {
+    /// Whether the client can send another message right now
+    pub fn try_acquire(&mut self, client_id: ClientId) -> bool {
+        self.buckets
+            .entry(client_id)
+            .or_insert_with(|| Bucket::full(BURST_SIZE, MESSAGES_PER_SEC))
+            .try_take()
+    }
+
+    /// Whether the client can send a proof chunk of the given size right now
+    pub fn try_acquire_proof_bytes(&mut self, client_id: ClientId, bytes: usize) -> bool {
+        self.proof_buckets
+            .entry(client_id)
+            .or_insert_with(|| Bucket::full(PROOF_BYTES_BURST, PROOF_BYTES_PER_SEC))
+            .try_take_many(bytes as f32)
+    }
+
+    /// Whether joins from the address are checked right now, too many recent wrong passwords
+    /// pause them
+    pub fn can_try_join(&mut self, ip: Option<IpAddr>) -> bool {
+        // Forget addresses that haven't failed for a while
+        self.rejected_joins.retain(|_, bucket| !bucket.is_full());
+        match self.rejected_joins.get_mut(&ip) {
+            Some(bucket) => bucket.has_token(),
+            None => true,
+        }
+    }
+
+    /// Count a wrong password against the limit of the address
+    pub fn record_rejected_join(&mut self, ip: Option<IpAddr>) {
+        self.rejected_joins
+            .entry(ip)
+            .or_insert_with(|| Bucket::full(REJECTED_JOIN_BURST, REJECTED_JOINS_PER_SEC))
+            .try_take();
+    }
+
+    pub fn remove(&mut self, client_id: ClientId) {
+        self.buckets.remove(&client_id);
+        self.proof_buckets.remove(&client_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::proof::PROOF_CHUNK_SIZE;
+
+    use super::*;
+
+    #[test]
+    fn rejected_joins_are_limited_per_address() {
+        let mut rate_limiter = RateLimiter::default();
+        let offender = Some("10.0.0.1".parse().unwrap());
+        let other = Some("10.0.0.2".parse().unwrap());
+        for _ in 0..REJECTED_JOIN_BURST as usize {
+            assert!(rate_limiter.can_try_join(offender));
+            rate_limiter.record_rejected_join(offender);
+        }
+        assert!(!rate_limiter.can_try_join(offender));
+        assert!(rate_limiter.can_try_join(other));
+        assert!(rate_limiter.can_try_join(None));
+    }
+
+    #[test]
+    fn proof_chunks_are_limited_by_size() {
+        let mut rate_limiter = RateLimiter::default();
+        let chunk = PROOF_CHUNK_SIZE;
+        for _ in 0..MAX_PROOF_IMAGE_SIZE as usize / chunk {
+            assert!(rate_limiter.try_acquire_proof_bytes(1, chunk));
+        }
+        assert!(!rate_limiter.try_acquire_proof_bytes(1, chunk));
+        assert!(rate_limiter.try_acquire_proof_bytes(2, chunk));
+        // Counted separately from other messages
+        assert!(rate_limiter.try_acquire(1));
+    }
+}
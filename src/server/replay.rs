@@ -0,0 +1,102 @@
+use std::{collections::HashMap, fs::File, io::Write, path::PathBuf, time::Instant};
+
+use bevy::prelude::*;
+use bevy_quinnet::shared::ClientId;
+
+use common::{
+    bingo::Board,
+    protocol::ClientProps,
+    replay::{ReplayEntry, ReplayEvent, ReplayHeader, REPLAY_EXTENSION, REPLAY_VERSION},
+    teams::Team,
+};
+
+/// Directory replay files are written to
+const REPLAY_DIR: &str = "replays";
+
+/// Records accepted game events of the current match to a replay file
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    header: Option<ReplayHeader>,
+    /// Created on the first event, so idle matches leave no file behind
+    file: Option<File>,
+    started: Option<Instant>,
+    winner: Option<Team>,
+}
+
+impl ReplayRecorder {
+    /// Finish the current replay and start recording a new match
+    pub fn start(&mut self, board: &Board, clients: &HashMap<ClientId, ClientProps>) {
+        self.flush();
+        *self = Self {
+            header: Some(ReplayHeader {
+                version: REPLAY_VERSION,
+                started_at: board.activity.started_at,
+                config: board.config.clone(),
+                activity: board.activity.clone(),
+                clients: clients.clone(),
+            }),
+            file: None,
+            started: Some(Instant::now()),
+            winner: None,
+        };
+    }
+
+    pub fn record(&mut self, event: ReplayEvent) {
+        let Some(started) = self.started else {
+            return;
+        };
+        let entry = ReplayEntry {
+            time_ms: started.elapsed().as_millis() as u64,
+            event,
+        };
+        if let Err(e) = self.write(&entry) {
+            warn!("Stopped recording replay due to error {}", e);
+            self.header = None;
+            self.file = None;
+            self.started = None;
+        }
+    }
+
+    /// Record the first win of the match
+    pub fn check_win(&mut self, board: &Board) {
+        if self.winner.is_some() {
+            return;
+        }
+        if let Some(team) = board.check_win() {
+            self.winner = Some(team);
+            self.record(ReplayEvent::Win(team));
+        }
+    }
+
+    pub fn flush(&mut self) {
+        if let Some(file) = &mut self.file {
+            if let Err(e) = file.sync_all() {
+                warn!("Failed to flush replay due to error {}", e);
+            }
+        }
+    }
+
+    fn write(&mut self, entry: &ReplayEntry) -> std::io::Result<()> {
+        if self.file.is_none() {
+            let Some(header) = &self.header else {
+                return Ok(());
+            };
+            std::fs::create_dir_all(REPLAY_DIR)?;
+            let mut file = File::create(Self::path(header))?;
+            writeln!(file, "{}", serde_json::to_string(header)?)?;
+            self.file = Some(file);
+        }
+        let file = self.file.as_mut().unwrap();
+        writeln!(file, "{}", serde_json::to_string(entry)?)
+    }
+
+    fn path(header: &ReplayHeader) -> PathBuf {
+        let mut path = PathBuf::from(REPLAY_DIR).join(format!("match-{}", header.started_at));
+        let mut index = 1;
+        while path.with_extension(REPLAY_EXTENSION).exists() {
+            index += 1;
+            path.set_file_name(format!("match-{}-{}", header.started_at, index));
+        }
+        path.with_extension(REPLAY_EXTENSION)
+    }
+}
@@ -0,0 +1,139 @@
+use std::time::{Duration, Instant};
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_quinnet::{server::Server, shared::AsyncRuntime};
+use tokio::task::JoinHandle;
+
+use common::{protocol::ServerMessage, BoardRes};
+
+use crate::{
+    access::Access,
+    broadcast,
+    persistence::{self, ReservedSessions},
+    replay::ReplayRecorder,
+    Clients,
+};
+
+/// Time given to clients to receive the shutdown notice before connections close
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+
+/// Request to notify clients and stop the server
+#[derive(Event)]
+pub struct ShutdownRequest {
+    pub reason: String,
+    /// Seconds after which clients can expect the server to be back
+    pub reconnect_after: Option<u32>,
+}
+
+pub struct ShutdownPlugin;
+
+impl Plugin for ShutdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShutdownRequest>()
+            .init_resource::<ShuttingDown>()
+            .add_systems(Startup, listen_for_signals)
+            .add_systems(
+                Update,
+                (detect_signals, begin_shutdown, finish_shutdown).chain(),
+            );
+    }
+}
+
+/// Tasks finishing when the process receives a termination signal
+#[derive(Resource)]
+struct SignalTasks(Vec<JoinHandle<()>>);
+
+/// Time the shutdown notice was sent
+#[derive(Resource, Default)]
+struct ShuttingDown(Option<Instant>);
+
+fn listen_for_signals(mut commands: Commands, runtime: Res<AsyncRuntime>) {
+    let mut tasks = vec![runtime.spawn(async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for interrupt signal due to error {}", e);
+            std::future::pending::<()>().await;
+        }
+    })];
+    #[cfg(unix)]
+    tasks.push(runtime.spawn(async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for terminate signal due to error {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    }));
+    commands.insert_resource(SignalTasks(tasks));
+}
+
+fn detect_signals(
+    mut commands: Commands,
+    tasks: Option<Res<SignalTasks>>,
+    mut requests: EventWriter<ShutdownRequest>,
+) {
+    let Some(tasks) = tasks else {
+        return;
+    };
+    if tasks.0.iter().any(JoinHandle::is_finished) {
+        info!("Received termination signal");
+        requests.send(ShutdownRequest {
+            reason: "Server is shutting down".to_owned(),
+            reconnect_after: None,
+        });
+        commands.remove_resource::<SignalTasks>();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn begin_shutdown(
+    mut requests: EventReader<ShutdownRequest>,
+    mut shutting_down: ResMut<ShuttingDown>,
+    mut server: ResMut<Server>,
+    mut recorder: ResMut<ReplayRecorder>,
+    board: Res<BoardRes>,
+    clients: Res<Clients>,
+    sessions: Res<ReservedSessions>,
+    access: Res<Access>,
+) {
+    let Some(request) = requests.read().last() else {
+        return;
+    };
+    if shutting_down.0.is_some() {
+        return;
+    }
+    info!("Shutting down: {}", request.reason);
+    broadcast(
+        server.endpoint_mut(),
+        &clients,
+        ServerMessage::ServerShutdown {
+            reason: request.reason.clone(),
+            reconnect_after: request.reconnect_after,
+        },
+    );
+    if let Err(e) = persistence::save(&board, &clients, &sessions, &access) {
+        warn!("Failed to save server state due to error {}", e);
+    }
+    recorder.flush();
+    shutting_down.0 = Some(Instant::now());
+}
+
+fn finish_shutdown(
+    shutting_down: Res<ShuttingDown>,
+    mut server: ResMut<Server>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let Some(start) = shutting_down.0 else {
+        return;
+    };
+    if start.elapsed() < SHUTDOWN_GRACE {
+        return;
+    }
+    if let Err(e) = server.stop_endpoint() {
+        warn!("Failed to close connections due to error {}", e);
+    }
+    app_exit.send(AppExit);
+}
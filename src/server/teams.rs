@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use bevy_quinnet::shared::ClientId;
+use rand::seq::SliceRandom;
+
+use common::teams::{Team, TeamSettings};
+
+use crate::Clients;
+
+/// Number of players in every active team
+fn team_sizes(clients: &Clients, settings: &TeamSettings) -> HashMap<Team, usize> {
+    let mut sizes = settings
+        .active()
+        .map(|team| (team, 0))
+        .collect::<HashMap<_, _>>();
+    for team in clients.data.values().filter_map(|client| client.team) {
+        if let Some(size) = sizes.get_mut(&team) {
+            *size += 1;
+        }
+    }
+    sizes
+}
+
+/// Whether the client may switch to the team, spectating is always allowed
+pub fn can_join(
+    clients: &Clients,
+    settings: &TeamSettings,
+    client_id: ClientId,
+    team: Option<Team>,
+) -> bool {
+    let Some(team) = team else {
+        return true;
+    };
+    let current = clients.data.get(&client_id).and_then(|client| client.team);
+    if current == Some(team) {
+        return true;
+    }
+    settings.is_active(team) && settings.has_room(team_sizes(clients, settings)[&team])
+}
+
+/// One of the active teams with the fewest players, `None` if all are full
+pub fn random_team(clients: &Clients, settings: &TeamSettings) -> Option<Team> {
+    let sizes = team_sizes(clients, settings);
+    let smallest = sizes
+        .values()
+        .copied()
+        .filter(|size| settings.has_room(*size))
+        .min()?;
+    let candidates = settings
+        .active()
+        .filter(|team| sizes[team] == smallest)
+        .collect::<Vec<_>>();
+    candidates.choose(&mut rand::thread_rng()).copied()
+}
+
+/// Move the latest joiners of the biggest teams until team sizes differ by at most one
+///
+/// Players that don't fit into any team become spectators, returns the moved clients.
+pub fn balance(clients: &mut Clients, settings: &TeamSettings) -> Vec<ClientId> {
+    let mut sizes = team_sizes(clients, settings);
+    let mut moved = Vec::new();
+    let players = clients
+        .join_order
+        .iter()
+        .rev()
+        .copied()
+        .filter(|id| clients.data[id].team.is_some())
+        .collect::<Vec<_>>();
+
+    let smallest = |sizes: &HashMap<Team, usize>| {
+        settings
+            .active()
+            .min_by_key(|team| sizes[team])
+            .filter(|team| settings.has_room(sizes[team]))
+    };
+
+    while let Some(biggest) = settings.active().max_by_key(|team| sizes[team]) {
+        let target = smallest(&sizes);
+        let over_limit = sizes[&biggest] > 0 && !settings.has_room(sizes[&biggest] - 1);
+        let uneven = target.is_some_and(|target| sizes[&biggest] > sizes[&target] + 1);
+        if !uneven && !over_limit {
+            break;
+        }
+        let Some(client_id) = players
+            .iter()
+            .copied()
+            .find(|id| clients.data[id].team == Some(biggest))
+        else {
+            break;
+        };
+        let client = clients.data.get_mut(&client_id).unwrap();
+        client.team = if uneven { target } else { None };
+        *sizes.get_mut(&biggest).unwrap() -= 1;
+        if let Some(team) = client.team {
+            *sizes.get_mut(&team).unwrap() += 1;
+        }
+        moved.push(client_id);
+    }
+    moved.sort();
+    moved.dedup();
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use common::protocol::ClientProps;
+
+    use super::*;
+
+    /// Clients joined in order, one per given team
+    fn clients(teams: &[Option<usize>]) -> Clients {
+        let mut clients = Clients::default();
+        for (id, team) in teams.iter().enumerate() {
+            let id = id as ClientId;
+            clients.data.insert(
+                id,
+                ClientProps {
+                    is_host: id == 0,
+                    is_referee: false,
+                    is_co_host: false,
+                    is_caster: false,
+                    username: format!("player{}", id),
+                    team: team.and_then(Team::new),
+                },
+            );
+            clients.join_order.push(id);
+        }
+        clients
+    }
+
+    fn settings(count: u8, max_size: Option<u8>) -> TeamSettings {
+        TeamSettings { count, max_size }
+    }
+
+    fn sizes(clients: &Clients, settings: &TeamSettings) -> Vec<usize> {
+        let sizes = team_sizes(clients, settings);
+        settings.active().map(|team| sizes[&team]).collect()
+    }
+
+    #[test]
+    fn can_join_full_team() {
+        let clients = clients(&[Some(0), Some(0), Some(1)]);
+        let settings = settings(2, Some(2));
+        assert!(!can_join(&clients, &settings, 2, Team::new(0)));
+        assert!(can_join(&clients, &settings, 0, Team::new(0)));
+        assert!(can_join(&clients, &settings, 0, Team::new(1)));
+        assert!(can_join(&clients, &settings, 0, None));
+        assert!(!can_join(&clients, &settings, 0, Team::new(2)));
+    }
+
+    #[test]
+    fn random_team_picks_smallest() {
+        let clients = clients(&[Some(0), Some(0), Some(1), Some(2)]);
+        for _ in 0..20 {
+            let team = random_team(&clients, &settings(4, None));
+            assert_eq!(team, Team::new(3));
+        }
+    }
+
+    #[test]
+    fn random_team_none_when_full() {
+        let clients = clients(&[Some(0), Some(1)]);
+        assert_eq!(random_team(&clients, &settings(2, Some(1))), None);
+        assert!(random_team(&clients, &settings(2, Some(2))).is_some());
+    }
+
+    #[test]
+    fn balance_evens_out_sizes() {
+        let mut clients = clients(&[Some(0), Some(0), Some(0), Some(0), Some(1), None]);
+        let settings = settings(3, None);
+        let moved = balance(&mut clients, &settings);
+        assert_eq!(sizes(&clients, &settings), vec![2, 2, 1]);
+        // The latest joiners of the biggest team are moved
+        assert_eq!(moved, vec![2, 3]);
+        assert_eq!(clients.data[&0].team, Team::new(0));
+        assert_eq!(clients.data[&5].team, None);
+    }
+
+    #[test]
+    fn balance_with_full_teams() {
+        let mut clients = clients(&[Some(0), Some(0), Some(0), Some(1), Some(1)]);
+        let settings = settings(2, Some(2));
+        let moved = balance(&mut clients, &settings);
+        assert_eq!(sizes(&clients, &settings), vec![2, 2]);
+        assert_eq!(moved, vec![2]);
+        assert_eq!(clients.data[&2].team, None);
+    }
+
+    #[test]
+    fn balance_moves_nobody_when_even() {
+        let mut clients = clients(&[Some(0), Some(1), Some(0)]);
+        assert!(balance(&mut clients, &settings(2, None)).is_empty());
+    }
+
+    #[test]
+    fn balance_without_room() {
+        // Unsanitized settings, as they could come from a hand-edited file
+        let mut clients = clients(&[Some(0), Some(1), None]);
+        assert_eq!(balance(&mut clients, &settings(2, Some(0))), vec![0, 1]);
+        assert!(clients.data.values().all(|client| client.team.is_none()));
+    }
+}